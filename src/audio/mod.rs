@@ -4,21 +4,43 @@
 
 use bevy::prelude::*;
 
+use crate::world::Weather;
+
+#[derive(Component)]
+struct AmbientSound;
+
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_sound);
+        app.add_systems(Update, follow_weather.run_if(resource_changed::<Weather>));
     }
 }
 
 fn setup_sound(asset_server: Res<AssetServer>, mut commands: Commands) {
-    commands.spawn(AudioBundle {
-        source: asset_server.load("embedded://ambient.mp3"),
-        settings: PlaybackSettings {
-            mode: bevy::audio::PlaybackMode::Loop,
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("embedded://ambient.mp3"),
+            settings: PlaybackSettings {
+                mode: bevy::audio::PlaybackMode::Loop,
+                ..default()
+            },
             ..default()
         },
-        ..default()
-    });
-}
\ No newline at end of file
+        AmbientSound,
+    ));
+}
+
+/// The wind howls louder as the weather worsens
+fn follow_weather(weather: Res<Weather>, query: Query<&AudioSink, With<AmbientSound>>) {
+    let volume = match *weather {
+        Weather::Clear => 1.0,
+        Weather::LightSnow => 1.15,
+        Weather::HeavySnow => 1.3,
+        Weather::Blizzard => 1.5,
+    };
+    for sink in query.iter() {
+        sink.set_volume(volume);
+    }
+}
@@ -0,0 +1,490 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Tuning of the game, loaded from `assets/game.config.ron`
+///
+/// The file is embedded in the binary, a copy in the `assets` folder next to
+/// the game takes precedence on native. With the `hot_reload` feature, the
+/// changes to the file apply while the game runs.
+use bevy::asset::io::Reader;
+use bevy::asset::{ron, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::headless::Headless;
+use crate::world::{default_patterns, DifficultyCurve, ObstaclePattern, MIN_GROUND_LENGTH};
+
+const CONFIG_PATH: &str = "game.config.ron";
+
+/// Every field is optional in the file, missing ones keep their default value
+#[derive(Asset, Resource, TypePath, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GameConfig {
+    pub mode: GameMode,
+    pub world: WorldConfig,
+    pub obstacle: ObstacleConfig,
+    pub difficulty: DifficultyCurve,
+    pub player: PlayerConfig,
+    pub wind: WindConfig,
+    pub weather: WeatherConfig,
+    pub daylight: DaylightConfig,
+    pub terrain: TerrainConfig,
+    /// Obstacle patterns the spawner picks from
+    pub patterns: Vec<ObstaclePattern>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            mode: GameMode::default(),
+            world: WorldConfig::default(),
+            obstacle: ObstacleConfig::default(),
+            difficulty: DifficultyCurve::default(),
+            player: PlayerConfig::default(),
+            wind: WindConfig::default(),
+            weather: WeatherConfig::default(),
+            daylight: DaylightConfig::default(),
+            terrain: TerrainConfig::default(),
+            patterns: default_patterns(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Reject the values the game can't run with, a hot-reloaded file with
+    /// such values keeps the previous configuration
+    pub fn validate(&self) -> Result<(), String> {
+        self.world.validate()?;
+        self.obstacle.validate()?;
+        self.difficulty.validate()?;
+        self.player.validate()?;
+        self.wind.validate()?;
+        self.weather.validate()?;
+        self.daylight.validate()?;
+        self.terrain.validate()?;
+        for pattern in &self.patterns {
+            pattern.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Durations, speeds and intervals end in timers or divide distances, they
+/// must be finite and above zero
+pub(crate) fn positive(name: &str, value: f32) -> Result<(), String> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{} {} is not positive", name, value))
+    }
+}
+
+fn not_negative(name: &str, value: f32) -> Result<(), String> {
+    if value >= 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{} {} is negative", name, value))
+    }
+}
+
+/// Upper end of a range, `min` being its lower end
+pub(crate) fn not_below(name: &str, value: f32, min: f32) -> Result<(), String> {
+    if value >= min && value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{} {} is below {}", name, value, min))
+    }
+}
+
+fn chance(name: &str, value: f32) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{} {} is not between 0 and 1", name, value))
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Flap through the gaps between the walls
+    #[default]
+    Flappy,
+    /// Run on the ground, jumping over the pits and the spikes
+    Running,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WorldConfig {
+    pub height: f32,
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        WorldConfig { height: 800.0 }
+    }
+}
+
+impl WorldConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("world.height", self.height)
+    }
+
+    /// Use the golden ratio for the world size
+    pub fn width(&self) -> f32 {
+        self.height * 1.618
+    }
+
+    pub fn top(&self) -> f32 {
+        self.height / 2.0
+    }
+
+    pub fn bottom(&self) -> f32 {
+        -self.top()
+    }
+
+    pub fn right(&self) -> f32 {
+        self.width() / 2.0
+    }
+
+    pub fn left(&self) -> f32 {
+        -self.right()
+    }
+}
+
+/// Speed, spawn interval and gap size follow the difficulty curve
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ObstacleConfig {
+    pub width: f32,
+    /// Drawn width of the walls, the collider keeps `width`
+    pub sprite_width: f32,
+    /// Seconds between two obstacles removed at the end of a run
+    pub despawn_interval: f32,
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        ObstacleConfig {
+            width: 20.0,
+            sprite_width: 28.0,
+            despawn_interval: 0.2,
+        }
+    }
+}
+
+impl ObstacleConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("obstacle.width", self.width)?;
+        positive("obstacle.sprite_width", self.sprite_width)?;
+        positive("obstacle.despawn_interval", self.despawn_interval)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PlayerConfig {
+    pub jump_velocity: f32,
+    /// Jump velocity in the running mode, where the fox only jumps from the
+    /// ground
+    pub running_jump_velocity: f32,
+    /// Share of the upward velocity kept when the jump is released early
+    pub jump_cut: f32,
+    /// Seconds a jump press waits for the jump to be possible
+    pub jump_buffer: f32,
+    /// Seconds the fox can still jump after leaving the ground
+    pub coyote_time: f32,
+    /// Jump in the air too, flapping like Flappy Bird, the running mode only
+    /// jumps from the ground
+    pub air_jump: bool,
+    /// Slide down the side of the walls instead of crashing into them
+    pub wall_slide: bool,
+    /// Fastest fall while sliding down a wall
+    pub wall_slide_speed: f32,
+    /// Speed away from the wall of a jump while sliding
+    pub wall_jump_push: f32,
+    pub gravity: f32,
+    /// Seconds per frame of the sprite animations
+    pub animation_frame_time: f32,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        PlayerConfig {
+            jump_velocity: 450.0,
+            running_jump_velocity: 650.0,
+            jump_cut: 0.5,
+            jump_buffer: 0.1,
+            coyote_time: 0.1,
+            air_jump: true,
+            wall_slide: false,
+            wall_slide_speed: 150.0,
+            wall_jump_push: 400.0,
+            gravity: -1500.0,
+            animation_frame_time: 0.1,
+        }
+    }
+}
+
+impl PlayerConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("player.jump_velocity", self.jump_velocity)?;
+        positive("player.running_jump_velocity", self.running_jump_velocity)?;
+        not_negative("player.jump_buffer", self.jump_buffer)?;
+        not_negative("player.coyote_time", self.coyote_time)?;
+        not_negative("player.wall_slide_speed", self.wall_slide_speed)?;
+        not_negative("player.wall_jump_push", self.wall_jump_push)?;
+        positive("player.animation_frame_time", self.animation_frame_time)
+    }
+}
+
+/// The wind changes at random intervals, to a gust or to a lull
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindConfig {
+    /// Strongest acceleration of a lull, and weakest of a gust
+    pub lull_strength: f32,
+    /// Strongest acceleration of a gust
+    pub gust_strength: f32,
+    /// Chance of the next change being a gust
+    pub gust_chance: f32,
+    /// Seconds the wind takes to change
+    pub min_change_time: f32,
+    pub max_change_time: f32,
+    /// Largest angle of the wind with the horizontal, in radians
+    pub max_angle: f32,
+}
+
+impl Default for WindConfig {
+    fn default() -> Self {
+        WindConfig {
+            lull_strength: 60.0,
+            gust_strength: 360.0,
+            gust_chance: 0.35,
+            min_change_time: 2.0,
+            max_change_time: 5.0,
+            max_angle: 0.35,
+        }
+    }
+}
+
+impl WindConfig {
+    fn validate(&self) -> Result<(), String> {
+        not_negative("wind.lull_strength", self.lull_strength)?;
+        not_below("wind.gust_strength", self.gust_strength, self.lull_strength)?;
+        chance("wind.gust_chance", self.gust_chance)?;
+        not_negative("wind.min_change_time", self.min_change_time)?;
+        not_below(
+            "wind.max_change_time",
+            self.max_change_time,
+            self.min_change_time,
+        )?;
+        not_negative("wind.max_angle", self.max_angle)
+    }
+}
+
+/// The weather gets one step better or worse at the end of each spell
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WeatherConfig {
+    /// Seconds a weather lasts
+    pub min_spell: f32,
+    pub max_spell: f32,
+    /// Chance of the weather worsening, at the easiest and the hardest level
+    pub easiest_worsen_chance: f32,
+    pub hardest_worsen_chance: f32,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        WeatherConfig {
+            min_spell: 15.0,
+            max_spell: 30.0,
+            easiest_worsen_chance: 0.4,
+            hardest_worsen_chance: 0.75,
+        }
+    }
+}
+
+impl WeatherConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("weather.min_spell", self.min_spell)?;
+        not_below("weather.max_spell", self.max_spell, self.min_spell)?;
+        chance("weather.easiest_worsen_chance", self.easiest_worsen_chance)?;
+        chance("weather.hardest_worsen_chance", self.hardest_worsen_chance)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DaylightConfig {
+    /// Seconds from a dawn to the next one
+    pub day_length: f32,
+}
+
+impl Default for DaylightConfig {
+    fn default() -> Self {
+        DaylightConfig { day_length: 240.0 }
+    }
+}
+
+impl DaylightConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("daylight.day_length", self.day_length)
+    }
+}
+
+/// Ground of the running mode, pits and platforms get wider as the
+/// difficulty rises
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TerrainConfig {
+    /// Height of the ground above the bottom of the world
+    pub ground_height: f32,
+    /// Length of the ground between two pits
+    pub min_ground_length: f32,
+    pub max_ground_length: f32,
+    /// Width of the pits, up to `max_pit_width` at the hardest level
+    pub min_pit_width: f32,
+    pub max_pit_width: f32,
+    /// Chance of spikes on a piece of ground
+    pub spike_chance: f32,
+    /// Chance of a floating platform in the middle of a pit twice as wide
+    pub platform_chance: f32,
+    pub platform_width: f32,
+    /// Height of the platforms above the ground
+    pub platform_height: f32,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        TerrainConfig {
+            ground_height: 80.0,
+            min_ground_length: 240.0,
+            max_ground_length: 640.0,
+            min_pit_width: 80.0,
+            max_pit_width: 150.0,
+            spike_chance: 0.4,
+            platform_chance: 0.3,
+            platform_width: 120.0,
+            platform_height: 60.0,
+        }
+    }
+}
+
+impl TerrainConfig {
+    fn validate(&self) -> Result<(), String> {
+        positive("terrain.ground_height", self.ground_height)?;
+        not_below(
+            "terrain.min_ground_length",
+            self.min_ground_length,
+            MIN_GROUND_LENGTH,
+        )?;
+        not_below(
+            "terrain.max_ground_length",
+            self.max_ground_length,
+            self.min_ground_length,
+        )?;
+        not_negative("terrain.min_pit_width", self.min_pit_width)?;
+        not_below(
+            "terrain.max_pit_width",
+            self.max_pit_width,
+            self.min_pit_width,
+        )?;
+        chance("terrain.spike_chance", self.spike_chance)?;
+        chance("terrain.platform_chance", self.platform_chance)?;
+        positive("terrain.platform_width", self.platform_width)?;
+        positive("terrain.platform_height", self.platform_height)
+    }
+}
+
+#[derive(Resource)]
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<GameConfig, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let config: GameConfig = ron::de::from_bytes(&bytes)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+/// Start with the default configuration, replaced once the file is loaded
+///
+/// The file is not loaded when running headless, the tests always run with
+/// the default configuration.
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameConfig>();
+        app.init_asset::<GameConfig>();
+        app.init_asset_loader::<GameConfigLoader>();
+        app.add_systems(
+            Startup,
+            load_config.run_if(not(resource_exists::<Headless>)),
+        );
+        app.add_systems(
+            Update,
+            update_config.run_if(resource_exists::<GameConfigHandle>),
+        );
+    }
+}
+
+fn load_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let path = if on_disk(CONFIG_PATH) {
+        CONFIG_PATH.to_string()
+    } else {
+        format!("embedded://{}", CONFIG_PATH)
+    };
+    info!("Load configuration from {}", path);
+    commands.insert_resource(GameConfigHandle(asset_server.load(path)));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn on_disk(path: &str) -> bool {
+    bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join("assets")
+        .join(path)
+        .exists()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn on_disk(_path: &str) -> bool {
+    false
+}
+
+fn update_config(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    assets: Res<Assets<GameConfig>>,
+    handle: Res<GameConfigHandle>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(loaded) = assets.get(&handle.0) {
+                info!("Configuration updated");
+                config.set_if_neq(loaded.clone());
+            }
+        }
+    }
+}
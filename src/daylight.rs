@@ -0,0 +1,102 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Time of day going from dawn to night and back during a run
+///
+/// The light tints the sprites marked `Daylit`. The texts and the particles
+/// keep their colors, so the score and the weather stay readable at night.
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::{ApplicationState, RestartEvent};
+
+/// Light at each point of the day, from 0 at dawn to 1 at the next dawn
+const LIGHT: [(f32, Color); 7] = [
+    (0.0, Color::srgb(1.0, 0.8, 0.75)),
+    (0.15, Color::WHITE),
+    (0.45, Color::WHITE),
+    (0.6, Color::srgb(1.0, 0.7, 0.55)),
+    (0.75, Color::srgb(0.4, 0.45, 0.7)),
+    (0.95, Color::srgb(0.4, 0.45, 0.7)),
+    (1.0, Color::srgb(1.0, 0.8, 0.75)),
+];
+
+/// Point of the day, from 0 at dawn to 1 at the next dawn
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct TimeOfDay(pub f32);
+
+impl TimeOfDay {
+    pub fn light(&self) -> Color {
+        let (mut from, mut to) = (LIGHT[0], LIGHT[0]);
+        for pair in LIGHT.windows(2) {
+            (from, to) = (pair[0], pair[1]);
+            if self.0 <= to.0 {
+                break;
+            }
+        }
+        let progress = ((self.0 - from.0) / (to.0 - from.0)).clamp(0.0, 1.0);
+        from.1.mix(&to.1, progress)
+    }
+}
+
+/// Sprite tinted by the light, `base` is its color in daylight
+#[derive(Component)]
+pub struct Daylit {
+    pub base: Color,
+}
+
+impl Default for Daylit {
+    fn default() -> Self {
+        Daylit { base: Color::WHITE }
+    }
+}
+
+pub struct DaylightPlugin;
+
+impl Plugin for DaylightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeOfDay>();
+        app.add_systems(
+            FixedUpdate,
+            advance_time.run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(Update, tint_sprites);
+        // A restarted run is back at dawn from its first tick
+        app.add_systems(PreUpdate, restart_event_handler);
+    }
+}
+
+fn advance_time(mut time_of_day: ResMut<TimeOfDay>, config: Res<GameConfig>, time: Res<Time>) {
+    let day_length = config.daylight.day_length.max(f32::EPSILON);
+    time_of_day.0 = (time_of_day.0 + time.delta_seconds() / day_length) % 1.0;
+}
+
+fn tint_sprites(time_of_day: Res<TimeOfDay>, mut query: Query<(&Daylit, &mut Sprite)>) {
+    let light = time_of_day.light().to_linear();
+    for (daylit, mut sprite) in query.iter_mut() {
+        let base = daylit.base.to_linear();
+        let color = Color::linear_rgba(
+            base.red * light.red,
+            base.green * light.green,
+            base.blue * light.blue,
+            base.alpha,
+        );
+        // Leave the sprites unchanged when the light is, not to recompute
+        // the slices of the walls
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// Start the new run at dawn
+fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        *time_of_day = TimeOfDay::default();
+    }
+}
@@ -0,0 +1,167 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Physics debug overlay, toggled with F3
+///
+/// Draws the colliders, the player velocity and acceleration, and a readout
+/// of the frame rate, the fixed tick rate, the entity count and the player
+/// state.
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::physics::{Collider, CollisionLayers, RigidBody, Shape};
+use crate::player::{Player, PlayerState};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+/// Length of the drawn vectors, per unit of velocity and acceleration
+const VELOCITY_SCALE: f32 = 0.2;
+const ACCELERATION_SCALE: f32 = 0.05;
+
+const PLAYER_COLOR: Color = Color::srgb(1.0, 1.0, 0.0);
+const OBSTACLE_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const WAYPOINT_COLOR: Color = Color::srgb(0.2, 1.0, 0.2);
+const GROUND_COLOR: Color = Color::srgb(1.0, 0.6, 0.2);
+const OTHER_COLOR: Color = Color::srgb(0.6, 0.6, 1.0);
+const VELOCITY_COLOR: Color = Color::srgb(0.0, 1.0, 1.0);
+const ACCELERATION_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+struct DebugText;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin);
+        app.init_resource::<DebugOverlay>();
+        app.add_systems(Startup, setup_debug);
+        app.add_systems(Update, toggle_overlay);
+        app.add_systems(
+            Update,
+            (draw_colliders, draw_player_vectors, update_debug_text)
+                .after(toggle_overlay)
+                .run_if(overlay_enabled),
+        );
+    }
+}
+
+fn overlay_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.enabled
+}
+
+fn setup_debug(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle::default()).with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        DebugText,
+    ));
+}
+
+fn toggle_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut query: Query<&mut Text, With<DebugText>>,
+) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        overlay.enabled = !overlay.enabled;
+        if !overlay.enabled {
+            query.single_mut().sections[0].value.clear();
+        }
+    }
+}
+
+fn layer_color(layer: CollisionLayers) -> Color {
+    match layer {
+        CollisionLayers::PLAYER => PLAYER_COLOR,
+        CollisionLayers::OBSTACLE => OBSTACLE_COLOR,
+        CollisionLayers::WAYPOINT => WAYPOINT_COLOR,
+        CollisionLayers::GROUND => GROUND_COLOR,
+        _ => OTHER_COLOR,
+    }
+}
+
+fn draw_colliders(mut gizmos: Gizmos, query: Query<(&Transform, &Collider)>) {
+    for (transform, collider) in query.iter() {
+        let center = transform.translation.truncate() + collider.offset;
+        let color = layer_color(collider.layer);
+        match collider.shape {
+            Shape::Aabb { half_size } => {
+                gizmos.rect_2d(center, 0.0, half_size * 2.0, color);
+                // Cross the waypoint zones, to tell them from the walls
+                if collider.layer.intersects(CollisionLayers::WAYPOINT) {
+                    gizmos.line_2d(center - half_size, center + half_size, color);
+                    gizmos.line_2d(
+                        center + Vec2::new(-half_size.x, half_size.y),
+                        center + Vec2::new(half_size.x, -half_size.y),
+                        color,
+                    );
+                }
+            }
+            Shape::Circle { radius } => {
+                gizmos.circle_2d(center, radius, color);
+            }
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => {
+                gizmos.primitive_2d(
+                    &Capsule2d::new(radius, half_length * 2.0),
+                    center,
+                    0.0,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+fn draw_player_vectors(mut gizmos: Gizmos, query: Query<(&Transform, &RigidBody), With<Player>>) {
+    for (transform, body) in query.iter() {
+        let position = transform.translation.truncate();
+        gizmos.arrow_2d(
+            position,
+            position + body.velocity * VELOCITY_SCALE,
+            VELOCITY_COLOR,
+        );
+        gizmos.arrow_2d(
+            position,
+            position + body.acceleration * ACCELERATION_SCALE,
+            ACCELERATION_COLOR,
+        );
+    }
+}
+
+fn update_debug_text(
+    diagnostics: Res<DiagnosticsStore>,
+    fixed_time: Res<Time<Fixed>>,
+    entities: Query<Entity>,
+    player_query: Query<(&Player, &PlayerState)>,
+    mut query: Query<&mut Text, With<DebugText>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let tick_rate = 1.0 / fixed_time.timestep().as_secs_f64();
+
+    let mut value = format!(
+        "FPS: {:.0}\nTick rate: {:.0} Hz\nEntities: {}",
+        fps,
+        tick_rate,
+        entities.iter().count()
+    );
+    if let Ok((player, state)) = player_query.get_single() {
+        value += &format!("\nState: {:?}\nAttitude: {:?}", state, player.attitude);
+    }
+    query.single_mut().sections[0].value = value;
+}
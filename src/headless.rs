@@ -0,0 +1,35 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Run the gameplay without window, rendering nor audio
+///
+/// Each `App::update` advances the time by exactly one fixed timestep, so
+/// every update after the first one runs `FixedUpdate` once. The keyboard
+/// controller is disabled, the `Controller` is driven by the caller.
+use bevy::asset::AssetPlugin;
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+/// Present when the game runs headless
+#[derive(Resource)]
+pub struct Headless;
+
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(StatesPlugin)
+            .add_plugins(InputPlugin)
+            .add_plugins(AssetPlugin::default())
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(
+                Time::<Fixed>::default().timestep(),
+            ))
+            .insert_resource(Headless);
+    }
+}
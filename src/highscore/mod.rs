@@ -0,0 +1,201 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::prelude::*;
+use bevy::utils::SystemTime;
+
+use crate::world::{ScoreBoard, WorldSeed};
+use crate::ApplicationState;
+
+mod storage;
+
+/// Number of runs kept in the table
+const HIGHSCORE_COUNT: usize = 5;
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighScore {
+    pub score: u32,
+    /// Seconds since the unix epoch
+    pub date: u64,
+    pub seed: u64,
+    /// Run duration in seconds
+    pub duration: f32,
+}
+
+impl HighScore {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split(';');
+        let entry = HighScore {
+            score: fields.next()?.parse().ok()?,
+            date: fields.next()?.parse().ok()?,
+            seed: fields.next()?.parse().ok()?,
+            duration: fields.next()?.parse().ok()?,
+        };
+        Some(entry)
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "{};{};{};{}",
+            self.score, self.date, self.seed, self.duration
+        )
+    }
+}
+
+/// Best runs, sorted from the highest score
+#[derive(Resource, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    /// Rank of the last run, if it entered the table
+    pub last_entry: Option<usize>,
+}
+
+impl HighScores {
+    pub fn parse(data: &str) -> Self {
+        let mut highscores = HighScores::default();
+        for entry in data.lines().filter_map(HighScore::parse) {
+            highscores.insert(entry);
+        }
+        highscores.last_entry = None;
+        highscores
+    }
+
+    pub fn serialize(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| entry.serialize() + "\n")
+            .collect()
+    }
+
+    /// Add a run to the table, and return its rank if it is a high-score
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        // On equal scores, the oldest run keeps its rank
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+
+        self.last_entry = if rank < HIGHSCORE_COUNT {
+            self.entries.insert(rank, entry);
+            self.entries.truncate(HIGHSCORE_COUNT);
+            Some(rank)
+        } else {
+            None
+        };
+        self.last_entry
+    }
+}
+
+#[derive(Component)]
+struct HighScoreUi;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let highscores = storage::load()
+            .map(|data| HighScores::parse(&data))
+            .unwrap_or_default();
+        app.insert_resource(highscores);
+        app.add_systems(Startup, setup_highscore);
+        app.add_systems(OnEnter(ApplicationState::GameEnding), record_highscore);
+        app.add_systems(OnEnter(ApplicationState::GameEnd), show_highscore);
+        app.add_systems(OnExit(ApplicationState::GameEnd), hide_highscore);
+    }
+}
+
+fn setup_highscore(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            transform: Transform::from_xyz(0.0, -120.0, 0.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HighScoreUi,
+    ));
+}
+
+fn record_highscore(
+    scoreboard: Res<ScoreBoard>,
+    seed: Res<WorldSeed>,
+    mut highscores: ResMut<HighScores>,
+) {
+    let date = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default();
+
+    let rank = highscores.insert(HighScore {
+        score: scoreboard.score,
+        date,
+        seed: seed.value,
+        duration: scoreboard.run_time,
+    });
+
+    if let Some(rank) = rank {
+        info!("New high-score at rank {}", rank + 1);
+        storage::save(&highscores.serialize());
+    }
+}
+
+fn show_highscore(
+    highscores: Res<HighScores>,
+    mut query: Query<(&mut Text, &mut Visibility), With<HighScoreUi>>,
+) {
+    let (mut text, mut visibility) = query.single_mut();
+
+    let mut sections = vec![TextSection::new("High-scores\n", TextStyle::default())];
+    for (rank, entry) in highscores.entries.iter().enumerate() {
+        let color = if highscores.last_entry == Some(rank) {
+            HIGHLIGHT_COLOR
+        } else {
+            Color::WHITE
+        };
+        sections.push(TextSection::new(
+            format!(
+                "{}. {:>4}   {}   {:>5.1}s   seed {}\n",
+                rank + 1,
+                entry.score,
+                format_date(entry.date),
+                entry.duration,
+                entry.seed
+            ),
+            TextStyle {
+                font_size: 20.0,
+                color,
+                ..default()
+            },
+        ));
+    }
+
+    text.sections = sections;
+    *visibility = Visibility::Visible;
+}
+
+fn hide_highscore(mut query: Query<&mut Visibility, With<HighScoreUi>>) {
+    *query.single_mut() = Visibility::Hidden;
+}
+
+/// Format seconds since the unix epoch as a `YYYY-MM-DD` date
+pub fn format_date(seconds: u64) -> String {
+    // Civil from days algorithm, from http://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,48 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Persist the high-score table between sessions
+///
+/// Native builds use a file in the working directory, the wasm build uses
+/// the browser localStorage.
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use bevy::prelude::*;
+
+    const HIGHSCORE_FILE: &str = "flynn-highscores.txt";
+
+    pub fn load() -> Option<String> {
+        std::fs::read_to_string(HIGHSCORE_FILE).ok()
+    }
+
+    pub fn save(data: &str) {
+        if let Err(err) = std::fs::write(HIGHSCORE_FILE, data) {
+            error!("Can't save high-scores to {}: {}", HIGHSCORE_FILE, err);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use bevy::prelude::*;
+
+    const HIGHSCORE_KEY: &str = "flynn-highscores";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn load() -> Option<String> {
+        local_storage()?.get_item(HIGHSCORE_KEY).ok()?
+    }
+
+    pub fn save(data: &str) {
+        let saved = local_storage().map(|storage| storage.set_item(HIGHSCORE_KEY, data));
+        if !matches!(saved, Some(Ok(()))) {
+            error!("Can't save high-scores to the local storage");
+        }
+    }
+}
+
+pub use backend::{load, save};
@@ -0,0 +1,146 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::prelude::*;
+
+pub mod audio;
+pub mod config;
+pub mod daylight;
+pub mod debug;
+pub mod headless;
+pub mod highscore;
+pub mod pause;
+pub mod physics;
+pub mod player;
+pub mod replay;
+pub mod world;
+
+use config::ConfigPlugin;
+use daylight::DaylightPlugin;
+use pause::PausePlugin;
+use physics::PhysicsPlugin;
+use player::PlayerPlugin;
+use replay::ReplayPlugin;
+use world::WorldPlugin;
+
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ApplicationState {
+    LandingScreen,
+    InGame,
+    Paused,
+    GameEnding,
+    GameEnd,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Event, Default)]
+pub struct RestartEvent {
+    /// Play the seed of the last run again, even when it isn't locked
+    pub same_seed: bool,
+}
+
+/// Gameplay of the game, without the rendering, audio and window setup
+///
+/// Runs along `DefaultPlugins` in the game, or `headless::HeadlessPlugin`
+/// in the tests.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_state(ApplicationState::LandingScreen)
+            .add_event::<RestartEvent>()
+            .add_systems(Startup, menu_setup)
+            .add_systems(Update, menu_control)
+            .add_plugins(ConfigPlugin)
+            .add_plugins(WorldPlugin)
+            .add_plugins(PlayerPlugin)
+            .add_plugins(PhysicsPlugin)
+            .add_plugins(ReplayPlugin)
+            .add_plugins(DaylightPlugin)
+            .add_plugins(PausePlugin);
+    }
+}
+
+/// Value following `name` on the command line, e.g. `--seed 42`
+pub fn command_line_arg(name: &str) -> Option<String> {
+    let mut args = std::env::args();
+    args.find(|arg| arg == name)?;
+    args.next()
+}
+
+fn menu_setup(mut commands: Commands) {
+    let text_style = TextStyle { ..default() };
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("Press \"Space\" to start", text_style),
+            // Above the pause overlay
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            ..default()
+        },
+        MenuText,
+    ));
+}
+
+fn menu_control(
+    state: Res<State<ApplicationState>>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    touches: Res<Touches>,
+    mut query: Query<&mut Text, With<MenuText>>,
+    mut event: EventWriter<RestartEvent>,
+) {
+    match state.get() {
+        ApplicationState::LandingScreen => {
+            if keyboard_input.pressed(KeyCode::Space) {
+                next_state.set(ApplicationState::InGame);
+            }
+            let touche = touches.first_pressed_position();
+            if touche.is_some() {
+                next_state.set(ApplicationState::InGame);
+            }
+        }
+        ApplicationState::InGame => {
+            let mut text = query.single_mut();
+            text.sections[0].value = "".to_string();
+        }
+        ApplicationState::Paused => {
+            let mut text = query.single_mut();
+            text.sections[0].value = if cfg!(target_arch = "wasm32") {
+                "Paused\n\nPress \"Escape\" to resume\nPress \"R\" to restart"
+            } else {
+                "Paused\n\nPress \"Escape\" to resume\nPress \"R\" to restart\nPress \"Q\" to quit"
+            }
+            .to_string();
+        }
+        ApplicationState::GameEnding => {
+            let mut text = query.single_mut();
+            text.sections[0].value = "You died...".to_string();
+        }
+        ApplicationState::GameEnd => {
+            let mut text = query.single_mut();
+            text.sections[0].value =
+                "Press \"Space\" to restart!\nPress \"R\" to retry the same course".to_string();
+
+            if keyboard_input.pressed(KeyCode::Space) {
+                next_state.set(ApplicationState::InGame);
+                event.send_default();
+            }
+
+            // Practice the seed, racing the ghost of its best run
+            if keyboard_input.just_pressed(KeyCode::KeyR) {
+                next_state.set(ApplicationState::InGame);
+                event.send(RestartEvent { same_seed: true });
+            }
+
+            let touche = touches.first_pressed_position();
+            if touche.is_some() {
+                next_state.set(ApplicationState::InGame);
+                event.send_default();
+            }
+        }
+    }
+}
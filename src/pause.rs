@@ -0,0 +1,151 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Pause the game during a run
+///
+/// The virtual time is paused along with the state, so the timers, the
+/// sprite animations and the wind particles freeze and resume where they
+/// left off.
+use bevy::prelude::*;
+
+use crate::{ApplicationState, RestartEvent};
+
+/// Top center of the screen, visible whatever the screen ratio
+const PAUSE_BUTTON_POSITION: Vec3 = Vec3::new(0.0, 330.0, 2.0);
+const PAUSE_BUTTON_SIZE: f32 = 80.0;
+
+#[derive(Component)]
+struct PauseButton;
+
+#[derive(Component)]
+struct PauseOverlay;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_pause);
+        app.add_systems(
+            Update,
+            pause_control.run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            resume_control.run_if(in_state(ApplicationState::Paused)),
+        );
+        app.add_systems(OnEnter(ApplicationState::InGame), show_pause_button);
+        app.add_systems(OnExit(ApplicationState::InGame), hide_pause_button);
+        app.add_systems(OnEnter(ApplicationState::Paused), pause);
+        app.add_systems(OnExit(ApplicationState::Paused), resume);
+    }
+}
+
+fn setup_pause(mut commands: Commands) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section("II", TextStyle::default()),
+            transform: Transform::from_translation(PAUSE_BUTTON_POSITION),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        PauseButton,
+    ));
+
+    // Darken the game behind the menu
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, 1.0),
+                scale: Vec3::new(4000.0, 4000.0, 0.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: Color::srgba(0.0, 0.0, 0.0, 0.6),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        PauseOverlay,
+    ));
+}
+
+fn pause_control(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    touches: Res<Touches>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::KeyP]) {
+        next_state.set(ApplicationState::Paused);
+    }
+
+    let (camera, camera_transform) = camera_query.single();
+    if touches
+        .iter_just_pressed()
+        .any(|touch| on_pause_button(camera, camera_transform, touch.position()))
+    {
+        next_state.set(ApplicationState::Paused);
+    }
+}
+
+/// Whether a touch at `position` on the screen is on the pause button, the
+/// controller leaves those touches to the button
+pub fn on_pause_button(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec2,
+) -> bool {
+    camera
+        .viewport_to_world_2d(camera_transform, position)
+        .is_some_and(|position| {
+            position.distance(PAUSE_BUTTON_POSITION.truncate()) < PAUSE_BUTTON_SIZE / 2.0
+        })
+}
+
+fn resume_control(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut touches: ResMut<Touches>,
+    mut next_state: ResMut<NextState<ApplicationState>>,
+    mut restart_event: EventWriter<RestartEvent>,
+    mut exit_event: EventWriter<AppExit>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Escape, KeyCode::KeyP])
+        || touches.any_just_pressed()
+    {
+        next_state.set(ApplicationState::InGame);
+        // The touch resuming the game doesn't make the fox jump
+        touches.release_all();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        next_state.set(ApplicationState::InGame);
+        restart_event.send_default();
+    }
+
+    // There is nothing to quit to in the browser
+    if cfg!(not(target_arch = "wasm32")) && keyboard_input.just_pressed(KeyCode::KeyQ) {
+        exit_event.send(AppExit::Success);
+    }
+}
+
+fn show_pause_button(mut query: Query<&mut Visibility, With<PauseButton>>) {
+    *query.single_mut() = Visibility::Visible;
+}
+
+fn hide_pause_button(mut query: Query<&mut Visibility, With<PauseButton>>) {
+    *query.single_mut() = Visibility::Hidden;
+}
+
+fn pause(mut time: ResMut<Time<Virtual>>, mut query: Query<&mut Visibility, With<PauseOverlay>>) {
+    info!("Pause");
+    time.pause();
+    *query.single_mut() = Visibility::Visible;
+}
+
+fn resume(mut time: ResMut<Time<Virtual>>, mut query: Query<&mut Visibility, With<PauseOverlay>>) {
+    info!("Resume");
+    time.unpause();
+    *query.single_mut() = Visibility::Hidden;
+}
@@ -2,15 +2,153 @@
  * Copyright (c) 2024 Louis Mayencourt
  */
 
-use bevy::math::bounding::{Aabb2d, IntersectsVolume};
+use std::ops::BitOr;
+
 use bevy::prelude::*;
 
-use crate::player::Player;
-use crate::world::Waypoint;
 use crate::ApplicationState;
 
-#[derive(Component)]
-pub struct Collider;
+/// Depth into a one-way collider still landing on it, for the bodies resting
+/// on its top
+const ONE_WAY_TOLERANCE: f32 = 1.0;
+
+/// Collision shape of an entity, independent of its sprite size
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Collider {
+    pub shape: Shape,
+    /// Position of the shape center, relative to the entity position
+    pub offset: Vec2,
+    /// Layer the entity belongs to
+    pub layer: CollisionLayers,
+    /// Layers the entity wants to be notified about
+    pub mask: CollisionLayers,
+    /// Layers the entity is pushed out of, when it has a `RigidBody`
+    pub blocking: CollisionLayers,
+    /// Only block the bodies coming from above, as a platform
+    pub one_way: bool,
+}
+
+/// Set of collision layers, one bit per kind of entity
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionLayers(pub u32);
+
+impl CollisionLayers {
+    pub const NONE: Self = CollisionLayers(0);
+    pub const PLAYER: Self = CollisionLayers(1 << 0);
+    pub const OBSTACLE: Self = CollisionLayers(1 << 1);
+    pub const WAYPOINT: Self = CollisionLayers(1 << 2);
+    pub const GROUND: Self = CollisionLayers(1 << 3);
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for CollisionLayers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        CollisionLayers(self.0 | other.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Aabb {
+        half_size: Vec2,
+    },
+    Circle {
+        radius: f32,
+    },
+    /// Vertical capsule, `half_length` is the half length of its straight part
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+}
+
+impl Collider {
+    pub fn aabb(half_size: Vec2) -> Self {
+        Collider {
+            shape: Shape::Aabb { half_size },
+            offset: Vec2::ZERO,
+            layer: CollisionLayers::NONE,
+            mask: CollisionLayers::NONE,
+            blocking: CollisionLayers::NONE,
+            one_way: false,
+        }
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Collider {
+            shape: Shape::Circle { radius },
+            offset: Vec2::ZERO,
+            layer: CollisionLayers::NONE,
+            mask: CollisionLayers::NONE,
+            blocking: CollisionLayers::NONE,
+            one_way: false,
+        }
+    }
+
+    pub fn capsule(half_length: f32, radius: f32) -> Self {
+        Collider {
+            shape: Shape::Capsule {
+                half_length,
+                radius,
+            },
+            offset: Vec2::ZERO,
+            layer: CollisionLayers::NONE,
+            mask: CollisionLayers::NONE,
+            blocking: CollisionLayers::NONE,
+            one_way: false,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Put the collider on `layer`, reporting contacts with the `mask` layers
+    pub fn with_layers(mut self, layer: CollisionLayers, mask: CollisionLayers) -> Self {
+        self.layer = layer;
+        self.mask = mask;
+        self
+    }
+
+    /// Push the entity out of the `blocking` layers, and stop its motion into them
+    pub fn with_blocking(mut self, blocking: CollisionLayers) -> Self {
+        self.blocking = blocking;
+        self
+    }
+
+    /// Let the bodies through, except when they come from above
+    pub fn one_way(mut self) -> Self {
+        self.one_way = true;
+        self
+    }
+}
+
+impl Shape {
+    /// Every shape is a box with rounded corners: the half size of its inner
+    /// box, and the radius of its corners.
+    fn rounded_box(&self) -> (Vec2, f32) {
+        match *self {
+            Shape::Aabb { half_size } => (half_size, 0.0),
+            Shape::Circle { radius } => (Vec2::ZERO, radius),
+            Shape::Capsule {
+                half_length,
+                radius,
+            } => (Vec2::new(0.0, half_length), radius),
+        }
+    }
+
+    /// Half size of the box bounding the shape
+    pub fn half_extents(&self) -> Vec2 {
+        let (half_size, radius) = self.rounded_box();
+        half_size + radius
+    }
+}
 
 #[derive(Component, Debug)]
 pub struct RigidBody {
@@ -29,16 +167,32 @@ impl Default for RigidBody {
     }
 }
 
-#[derive(Event, Default)]
+impl RigidBody {
+    /// Distance travelled during the last tick of `delta_t`
+    pub fn displacement(&self, delta_t: f32) -> Vec2 {
+        // The velocity is the one at the end of the tick
+        self.velocity * delta_t - 0.5 * self.acceleration * delta_t * delta_t
+    }
+}
+
+/// Contact seen from `entity`, sent when its mask includes the other layer
+#[derive(Event, Debug, Clone, Copy)]
 pub struct CollideEvent {
-    pub other: CollideWith,
+    pub entity: Entity,
+    pub layer: CollisionLayers,
+    pub other: Entity,
+    pub other_layer: CollisionLayers,
+    /// Fraction of the tick at which the bodies first touched
+    pub time_of_impact: f32,
+    /// Normal of the touched face of the other body, pointing toward `entity`
+    pub normal: Vec2,
 }
 
-#[derive(Default)]
-pub enum CollideWith {
-    #[default]
-    Obstacle,
-    Waypoint(Entity),
+/// First contact between two shapes moving during a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub time_of_impact: f32,
+    pub normal: Vec2,
 }
 
 pub struct PhysicsPlugin;
@@ -52,12 +206,14 @@ impl Plugin for PhysicsPlugin {
         );
         app.add_systems(
             FixedUpdate,
-            collision.run_if(in_state(ApplicationState::InGame)),
+            collision
+                .after(bodies_movement)
+                .run_if(in_state(ApplicationState::InGame)),
         );
     }
 }
 
-fn bodies_movement(mut query: Query<(&mut RigidBody, &mut Transform)>, time: Res<Time>) {
+pub fn bodies_movement(mut query: Query<(&mut RigidBody, &mut Transform)>, time: Res<Time>) {
     for (mut body, mut transform) in query.iter_mut() {
         let delta_t = time.delta_seconds();
         // Apply MRUA equation
@@ -74,42 +230,270 @@ fn bodies_movement(mut query: Query<(&mut RigidBody, &mut Transform)>, time: Res
     }
 }
 
-fn collision(
-    mut obstacles_query: Query<&Transform, (Without<Player>, With<Collider>)>,
-    waypoint_query: Query<(&Transform, Entity), (Without<Player>, With<Waypoint>)>,
-    mut player_query: Query<&Transform, With<Player>>,
+pub fn collision(
+    mut query: Query<(Entity, &mut Transform, &Collider, Option<&mut RigidBody>)>,
     mut collision_events: EventWriter<CollideEvent>,
-    // gizmos: Gizmos,
+    time: Res<Time>,
 ) {
-    let player_transform = player_query.single_mut();
-    let player_box = Aabb2d::new(
-        player_transform.translation.truncate(),
-        player_transform.scale.truncate() * 6.0,
-    );
-    for obstacle in obstacles_query.iter_mut() {
-        let obstacle_box = Aabb2d::new(
-            obstacle.translation.truncate(),
-            obstacle.scale.truncate() / 2.0,
-        );
-
-        if player_box.intersects(&obstacle_box) {
-            collision_events.send_default();
+    let delta_t = time.delta_seconds();
+    let mut combinations = query.iter_combinations_mut();
+    while let Some([a, b]) = combinations.fetch_next() {
+        let (a_entity, mut a_transform, a_collider, mut a_body) = a;
+        let (b_entity, mut b_transform, b_collider, mut b_body) = b;
+        let a_notified = a_collider.mask.intersects(b_collider.layer);
+        let b_notified = b_collider.mask.intersects(a_collider.layer);
+        let a_blocked = a_collider.blocking.intersects(b_collider.layer) && a_body.is_some();
+        let b_blocked = b_collider.blocking.intersects(a_collider.layer) && b_body.is_some();
+        if !a_notified && !b_notified && !a_blocked && !b_blocked {
+            continue;
         }
 
-        // gizmos.rect_2d(player_box.center(), 0.0, player_box.half_size() *2.0, Color::GRAY);
-    }
+        // Test the bodies from where they were at the start of the tick
+        let a_displacement = a_body
+            .as_deref()
+            .map_or(Vec2::ZERO, |body| body.displacement(delta_t));
+        let b_displacement = b_body
+            .as_deref()
+            .map_or(Vec2::ZERO, |body| body.displacement(delta_t));
+        let a_start = a_transform.translation.truncate() + a_collider.offset - a_displacement;
+        let b_start = b_transform.translation.truncate() + b_collider.offset - b_displacement;
 
-    for (waypoint, entity) in waypoint_query.iter() {
-        let waypoint_box = Aabb2d::new(
-            waypoint.translation.truncate(),
-            waypoint.scale.truncate() / 2.0,
-        );
+        let Some(contact) = sweep(
+            a_start,
+            &a_collider.shape,
+            a_displacement - b_displacement,
+            b_start,
+            &b_collider.shape,
+        ) else {
+            continue;
+        };
 
-        if player_box.intersects(&waypoint_box) {
+        // A one-way collider is not there for the bodies not coming from above
+        let a_start_offset = a_start - b_start;
+        if b_collider.one_way
+            && !from_above(
+                a_start_offset,
+                &a_collider.shape,
+                &b_collider.shape,
+                contact.normal,
+            )
+        {
+            continue;
+        }
+        if a_collider.one_way
+            && !from_above(
+                -a_start_offset,
+                &b_collider.shape,
+                &a_collider.shape,
+                -contact.normal,
+            )
+        {
+            continue;
+        }
+
+        let a_velocity = a_body.as_deref().map_or(Vec2::ZERO, |body| body.velocity);
+        let b_velocity = b_body.as_deref().map_or(Vec2::ZERO, |body| body.velocity);
+        let a_center = a_transform.translation.truncate() + a_collider.offset;
+        let b_center = b_transform.translation.truncate() + b_collider.offset;
+        if let (true, Some(body)) = (a_blocked, a_body.as_deref_mut()) {
+            let other = (b_center, &b_collider.shape, b_velocity);
+            resolve(body, a_center, &a_collider.shape, other, contact.normal);
+            a_transform.translation.x = body.position.x;
+            a_transform.translation.y = body.position.y;
+        }
+        if let (true, Some(body)) = (b_blocked, b_body.as_deref_mut()) {
+            let other = (a_center, &a_collider.shape, a_velocity);
+            resolve(body, b_center, &b_collider.shape, other, -contact.normal);
+            b_transform.translation.x = body.position.x;
+            b_transform.translation.y = body.position.y;
+        }
+
+        if a_notified {
             collision_events.send(CollideEvent {
-                other: CollideWith::Waypoint(entity),
+                entity: a_entity,
+                layer: a_collider.layer,
+                other: b_entity,
+                other_layer: b_collider.layer,
+                time_of_impact: contact.time_of_impact,
+                normal: contact.normal,
             });
-            // collision_events.send_default();
         }
+        if b_notified {
+            collision_events.send(CollideEvent {
+                entity: b_entity,
+                layer: b_collider.layer,
+                other: a_entity,
+                other_layer: a_collider.layer,
+                time_of_impact: contact.time_of_impact,
+                normal: -contact.normal,
+            });
+        }
+    }
+}
+
+/// Distance to move the `moving` shape along `normal` to get it out of
+/// `other`, `offset` is from the center of `other` to the center of `moving`
+pub fn penetration(offset: Vec2, moving: &Shape, other: &Shape, normal: Vec2) -> f32 {
+    let (moving_half_size, moving_radius) = moving.rounded_box();
+    let (other_half_size, other_radius) = other.rounded_box();
+    // Reach of the Minkowski sum of the two rounded boxes along the normal
+    let reach =
+        (moving_half_size + other_half_size).dot(normal.abs()) + moving_radius + other_radius;
+    reach - offset.dot(normal)
+}
+
+/// Touching the top of `other`, without being into it at the start of the tick
+fn from_above(start_offset: Vec2, moving: &Shape, other: &Shape, normal: Vec2) -> bool {
+    normal.y > normal.x.abs()
+        && penetration(start_offset, moving, other, Vec2::Y) <= ONE_WAY_TOLERANCE
+}
+
+/// Push the `body` out of the `other` shape along the contact normal, and
+/// stop its motion into it, keeping the motion along the surface
+///
+/// `other` is the center, the shape and the velocity of the other body, it is
+/// not pushed back.
+pub fn resolve(
+    body: &mut RigidBody,
+    center: Vec2,
+    shape: &Shape,
+    (other_center, other, other_velocity): (Vec2, &Shape, Vec2),
+    normal: Vec2,
+) {
+    let depth = penetration(center - other_center, shape, other, normal);
+    if depth > 0.0 {
+        body.position += normal * depth;
+    }
+    let approach = (body.velocity - other_velocity).dot(normal);
+    if approach < 0.0 {
+        body.velocity -= normal * approach;
+    }
+}
+
+/// Swept test of the `moving` shape travelling by `displacement` against `other`
+///
+/// Both shapes are given at their center at the start of the tick, the
+/// displacement is relative to `other`. Shapes already overlapping at the start
+/// of the tick touch at time 0.
+pub fn sweep(
+    moving_center: Vec2,
+    moving: &Shape,
+    displacement: Vec2,
+    other_center: Vec2,
+    other: &Shape,
+) -> Option<Contact> {
+    // The Minkowski sum of two rounded boxes is a rounded box, so the test
+    // reduces to a ray cast from the moving center against their sum.
+    let (moving_half_size, moving_radius) = moving.rounded_box();
+    let (other_half_size, other_radius) = other.rounded_box();
+    let half_size = moving_half_size + other_half_size;
+    let radius = moving_radius + other_radius;
+    let start = moving_center - other_center;
+
+    match (moving, other) {
+        (Shape::Aabb { .. }, Shape::Aabb { .. }) => sweep_box(start, displacement, half_size),
+        _ => sweep_rounded_box(start, displacement, half_size, radius),
+    }
+}
+
+/// Ray cast from `start` against the box of `half_size` centered on the origin
+fn sweep_box(start: Vec2, displacement: Vec2, half_size: Vec2) -> Option<Contact> {
+    let penetration = half_size - start.abs();
+    if penetration.x >= 0.0 && penetration.y >= 0.0 {
+        // Already overlapping, push out along the axis of least penetration
+        let normal = if penetration.x < penetration.y {
+            Vec2::new(start.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, start.y.signum())
+        };
+        return Some(Contact {
+            time_of_impact: 0.0,
+            normal,
+        });
+    }
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+    for axis in [Vec2::X, Vec2::Y] {
+        let start = start.dot(axis);
+        let half_size = half_size.dot(axis);
+        let displacement = displacement.dot(axis);
+
+        if displacement == 0.0 {
+            // Never overlapping on this axis
+            if start.abs() > half_size {
+                return None;
+            }
+            continue;
+        }
+
+        let near = (-half_size.copysign(displacement) - start) / displacement;
+        let far = (half_size.copysign(displacement) - start) / displacement;
+        if near > entry {
+            entry = near;
+            normal = -axis * displacement.signum();
+        }
+        exit = exit.min(far);
+    }
+
+    if entry <= exit && (0.0..=1.0).contains(&entry) {
+        Some(Contact {
+            time_of_impact: entry,
+            normal,
+        })
+    } else {
+        None
+    }
+}
+
+/// Ray cast from `start` against the box of `half_size` with corners rounded
+/// by `radius`, centered on the origin
+fn sweep_rounded_box(
+    start: Vec2,
+    displacement: Vec2,
+    half_size: Vec2,
+    radius: f32,
+) -> Option<Contact> {
+    let outside = (start.abs() - half_size).max(Vec2::ZERO);
+    if outside == Vec2::ZERO {
+        // Center inside the inner box
+        return sweep_box(start, Vec2::ZERO, half_size + radius);
+    }
+    if outside.length() <= radius {
+        return Some(Contact {
+            time_of_impact: 0.0,
+            normal: (outside * start.signum()).normalize(),
+        });
+    }
+
+    // Hit the box grown by the radius, then check whether it is on a corner
+    let contact = sweep_box(start, displacement, half_size + radius)?;
+    let hit = start + displacement * contact.time_of_impact;
+    if hit.x.abs() <= half_size.x || hit.y.abs() <= half_size.y {
+        return Some(contact);
+    }
+
+    let corner = half_size * hit.signum();
+    sweep_circle(start - corner, displacement, radius)
+}
+
+/// Ray cast from `start` against the circle of `radius` centered on the origin
+fn sweep_circle(start: Vec2, displacement: Vec2, radius: f32) -> Option<Contact> {
+    let a = displacement.length_squared();
+    let b = start.dot(displacement);
+    let c = start.length_squared() - radius * radius;
+    let discriminant = b * b - a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let time_of_impact = (-b - discriminant.sqrt()) / a;
+    if !(0.0..=1.0).contains(&time_of_impact) {
+        return None;
     }
+    Some(Contact {
+        time_of_impact,
+        normal: (start + displacement * time_of_impact).normalize(),
+    })
 }
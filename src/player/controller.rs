@@ -5,31 +5,80 @@
 /// Define the controls required to play the game
 use bevy::prelude::*;
 
-#[derive(Debug, PartialEq)]
+use crate::pause::on_pause_button;
+
+/// Distance a touch moves to be a swipe, dashing toward the swipe
+const SWIPE_DISTANCE: f32 = 60.0;
+/// Smallest share of the swipe along an axis to dash along it, about the
+/// sine of 22.5 degrees, for the eight directions of the arrow keys
+const SWIPE_AXIS_SHARE: f32 = 0.38;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
     None,
-    Jump,
+    /// Dash toward the controller direction
+    Dash,
 }
 
 #[derive(Component)]
 pub struct Controller {
     pub direction: Vec2,
     pub action: Action,
+    /// Held apart from the action, the jump is triggered on its press
+    pub jump_held: bool,
 }
 
 /// Controller implementation for keyboard
 pub fn keyboard_inputs(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     touches: Res<Touches>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
     mut query: Query<&mut Controller>,
 ) {
     let mut controller = query.single_mut();
 
-    let touche = touches.first_pressed_position();
-    if keyboard_input.pressed(KeyCode::Space) || keyboard_input.pressed(KeyCode::ArrowUp) || touche.is_some() {
-        // controller.direction.y = 1.0;
-        controller.action = Action::Jump;
+    let pressed = |key_code: KeyCode| {
+        if keyboard_input.pressed(key_code) {
+            1.0
+        } else {
+            0.0
+        }
+    };
+    controller.direction = Vec2::new(
+        pressed(KeyCode::ArrowRight) - pressed(KeyCode::ArrowLeft),
+        pressed(KeyCode::ArrowUp) - pressed(KeyCode::ArrowDown),
+    );
+
+    // Touches started on the pause button are left to the button
+    let (camera, camera_transform) = camera_query.single();
+    let touche = touches
+        .iter()
+        .find(|touch| !on_pause_button(camera, camera_transform, touch.start_position()));
+    // The screen y axis points down
+    let swipe = touche
+        .map(|touch| touch.distance() * Vec2::new(1.0, -1.0))
+        .filter(|swipe| swipe.length() > SWIPE_DISTANCE);
+    if let Some(swipe) = swipe {
+        let snap = |value: f32| {
+            if value.abs() > swipe.length() * SWIPE_AXIS_SHARE {
+                value.signum()
+            } else {
+                0.0
+            }
+        };
+        controller.direction = Vec2::new(snap(swipe.x), snap(swipe.y));
+    }
+
+    let dash_held =
+        keyboard_input.pressed(KeyCode::KeyX) || keyboard_input.pressed(KeyCode::ShiftLeft);
+    if dash_held || swipe.is_some() {
+        controller.action = Action::Dash;
     } else {
         controller.action = Action::None;
     }
+    // Up is only a dash direction while the dash key is held, and a touch
+    // stops jumping once it is a swipe
+    controller.jump_held = keyboard_input.pressed(KeyCode::Space)
+        || (keyboard_input.pressed(KeyCode::ArrowUp) && !dash_held)
+        || (touche.is_some() && swipe.is_none());
 }
@@ -0,0 +1,127 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Short burst of the fox in the held direction, as in Celeste
+///
+/// The dash ignores the gravity and the wind while it lasts. Dashing uses it
+/// up, flying through a gap refills it.
+use bevy::prelude::*;
+use bevy_particle_systems::*;
+
+use crate::physics::RigidBody;
+use crate::player::*;
+
+pub const DASH_SPEED: f32 = 700.0;
+/// Fixed ticks the dash lasts
+pub const DASH_TICKS: u32 = 10;
+/// Share of the dash speed kept once it ends
+const DASH_END_FACTOR: f32 = 0.3;
+
+#[derive(Component, Debug)]
+pub struct Dash {
+    pub available: bool,
+    /// Dash held on the previous tick
+    held: bool,
+    ticks_left: u32,
+    direction: Vec2,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Dash {
+            available: true,
+            held: false,
+            ticks_left: 0,
+            direction: Vec2::ZERO,
+        }
+    }
+}
+
+impl Dash {
+    pub fn is_dashing(&self) -> bool {
+        self.ticks_left > 0
+    }
+
+    /// Follow the dash input of the tick, true only on its press, holding
+    /// the dash doesn't dash again once refilled
+    pub fn pressed(&mut self, held: bool) -> bool {
+        let pressed = held && !self.held;
+        self.held = held;
+        pressed
+    }
+
+    /// Dash forward when no direction is held
+    pub fn start(&mut self, direction: Vec2) {
+        self.available = false;
+        self.ticks_left = DASH_TICKS;
+        self.direction = if direction == Vec2::ZERO {
+            Vec2::X
+        } else {
+            direction.normalize()
+        };
+    }
+
+    /// Move at the dash speed, slowing down on the last tick
+    pub fn tick(&mut self, body: &mut RigidBody) {
+        self.ticks_left -= 1;
+        body.velocity = self.direction * DASH_SPEED;
+        if self.ticks_left == 0 {
+            body.velocity *= DASH_END_FACTOR;
+        }
+        body.acceleration = Vec2::ZERO;
+    }
+}
+
+/// Snow dust left behind while dashing
+#[derive(Component)]
+pub struct DashTrail;
+
+pub fn spawn_trail(commands: &mut Commands) {
+    commands.spawn((
+        ParticleSystemBundle {
+            particle_system: ParticleSystem {
+                max_particles: 256,
+                emitter_shape: EmitterShape::CircleSegment(CircleSegment {
+                    radius: JitteredValue::jittered(10.0, -10.0..0.0),
+                    opening_angle: std::f32::consts::TAU,
+                    direction_angle: 0.0,
+                }),
+                spawn_rate_per_second: 200.0.into(),
+                initial_speed: JitteredValue::jittered(20.0, -20.0..0.0),
+                lifetime: JitteredValue::jittered(0.4, -0.1..0.1),
+                color: ColorOverTime::Gradient(Curve::new(vec![
+                    CurvePoint::new(Color::srgba(0.8, 0.9, 1.0, 0.8), 0.0),
+                    CurvePoint::new(Color::srgba(0.8, 0.9, 1.0, 0.0), 1.0),
+                ])),
+                initial_scale: JitteredValue::jittered(3.0, -1.0..1.0),
+                looping: true,
+                system_duration_seconds: 10.0,
+                ..ParticleSystem::default()
+            },
+            transform: Transform::from_translation(PLAYER_START_POSITION.extend(-0.1)),
+            ..ParticleSystemBundle::default()
+        },
+        DashTrail,
+    ));
+}
+
+/// Emit the trail behind the fox only while it dashes
+pub fn follow_player(
+    mut commands: Commands,
+    player: Query<(&Transform, &Dash), Without<DashTrail>>,
+    mut trail: Query<(Entity, &mut Transform, Has<Playing>), With<DashTrail>>,
+) {
+    let Ok((player_transform, dash)) = player.get_single() else {
+        return;
+    };
+    for (entity, mut transform, playing) in trail.iter_mut() {
+        transform.translation.x = player_transform.translation.x;
+        transform.translation.y = player_transform.translation.y;
+        if dash.is_dashing() && !playing {
+            commands.entity(entity).insert(Playing);
+        } else if !dash.is_dashing() && playing {
+            commands.entity(entity).remove::<Playing>();
+        }
+    }
+}
@@ -0,0 +1,142 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Ghost of the best run on a seed, running alongside the player
+///
+/// The ghost only follows a stored trajectory. It has no `Collider` nor
+/// `RigidBody`, so it never takes part in the physics.
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::daylight::Daylit;
+use crate::player::*;
+use crate::world::{ScoreBoard, WorldSeed};
+
+const GHOST_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+/// Position and animation of the fox for every fixed tick of a run
+#[derive(Default, Clone)]
+pub struct Trajectory {
+    pub score: u32,
+    pub frames: Vec<(Vec2, PlayerState)>,
+}
+
+/// Best trajectory recorded for each seed
+#[derive(Resource, Default)]
+pub struct BestRuns(pub HashMap<u64, Trajectory>);
+
+/// Trajectory of the current run
+#[derive(Resource, Default)]
+pub struct TrajectoryRecorder(pub Trajectory);
+
+#[derive(Component)]
+pub struct Ghost {
+    tick: usize,
+}
+
+pub fn spawn_ghost(
+    commands: &mut Commands,
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    frame_time: f32,
+) {
+    commands.spawn((
+        SpriteBundle {
+            texture,
+            sprite: Sprite {
+                color: GHOST_COLOR,
+                ..default()
+            },
+            // Behind the player
+            transform: Transform::from_translation(PLAYER_START_POSITION.extend(-0.5))
+                .with_scale(Vec3::splat(4.0)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        TextureAtlas {
+            layout,
+            index: SPRITE_IDLE_IDX.0,
+        },
+        AnimationIndices {
+            first: SPRITE_IDLE_IDX.0,
+            last: SPRITE_IDLE_IDX.1,
+        },
+        AnimationTimer(Timer::from_seconds(frame_time, TimerMode::Repeating)),
+        PlayerState::Idle,
+        Daylit { base: GHOST_COLOR },
+        Ghost { tick: 0 },
+    ));
+}
+
+/// Start the ghost and the recording from the first tick of the new run
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut recorder: ResMut<TrajectoryRecorder>,
+    mut query: Query<&mut Ghost>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        recorder.0 = Trajectory::default();
+        query.single_mut().tick = 0;
+    }
+}
+
+pub fn record_trajectory(
+    mut recorder: ResMut<TrajectoryRecorder>,
+    query: Query<(&RigidBody, &PlayerState), With<Player>>,
+) {
+    let (body, state) = query.single();
+    recorder.0.frames.push((body.position, *state));
+}
+
+pub fn ghost_movement(
+    seed: Res<WorldSeed>,
+    best_runs: Res<BestRuns>,
+    mut query: Query<(
+        &mut Ghost,
+        &mut Transform,
+        &mut PlayerState,
+        &mut Visibility,
+    )>,
+) {
+    let (mut ghost, mut transform, mut state, mut visibility) = query.single_mut();
+
+    // Only race the best run of the seed being played
+    let frame = best_runs
+        .0
+        .get(&seed.value)
+        .and_then(|best_run| best_run.frames.get(ghost.tick));
+
+    match frame {
+        Some((position, frame_state)) => {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+            *state = *frame_state;
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+    ghost.tick += 1;
+}
+
+/// Keep the trajectory of the run if it is the best on its seed
+pub fn end_run(
+    seed: Res<WorldSeed>,
+    scoreboard: Res<ScoreBoard>,
+    mut best_runs: ResMut<BestRuns>,
+    mut recorder: ResMut<TrajectoryRecorder>,
+) {
+    let is_best = match best_runs.0.get(&seed.value) {
+        Some(best_run) => scoreboard.score > best_run.score,
+        None => true,
+    };
+
+    if is_best {
+        info!("New best run on seed {}", seed.value);
+        let mut trajectory = std::mem::take(&mut recorder.0);
+        trajectory.score = scoreboard.score;
+        best_runs.0.insert(seed.value, trajectory);
+    }
+}
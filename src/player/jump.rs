@@ -0,0 +1,85 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Jump triggered on the press, higher the longer it is held
+///
+/// A press waits up to the jump buffer for the jump to be possible, and the
+/// fox can still jump for the coyote time after leaving the ground. Releasing
+/// the jump while rising cuts the upward velocity.
+use bevy::prelude::*;
+
+use crate::config::PlayerConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JumpAction {
+    None,
+    Start,
+    Cut,
+}
+
+#[derive(Component, Debug, Default)]
+pub struct Jump {
+    /// Jump held on the previous tick
+    held: bool,
+    /// Seconds since the press still waiting to jump
+    buffered: Option<f32>,
+    /// Seconds since the fox left the ground, none while standing on it
+    airborne: Option<f32>,
+    /// Rising from a jump still held
+    rising: bool,
+}
+
+impl Jump {
+    /// Standing on the ground, the coyote time starts once leaving it
+    pub fn land(&mut self) {
+        self.airborne = None;
+    }
+
+    pub fn is_rising(&self) -> bool {
+        self.rising
+    }
+
+    /// Follow the jump input of the tick, `possible` is false while the fox
+    /// can't jump at all, e.g. while dashing, `air_jump` lets it jump in the
+    /// air too, flapping like Flappy Bird
+    pub fn update(
+        &mut self,
+        held: bool,
+        possible: bool,
+        air_jump: bool,
+        delta_t: f32,
+        config: &PlayerConfig,
+    ) -> JumpAction {
+        let pressed = held && !self.held;
+        let released = !held && self.held;
+        self.held = held;
+
+        self.buffered = match self.buffered {
+            _ if pressed => Some(0.0),
+            Some(age) if age + delta_t <= config.jump_buffer => Some(age + delta_t),
+            _ => None,
+        };
+        let airborne = self.airborne.map_or(0.0, |time| time + delta_t);
+        self.airborne = Some(airborne);
+
+        let can_jump = possible && (air_jump || airborne <= config.coyote_time);
+        if self.buffered.is_some() && can_jump {
+            self.buffered = None;
+            // Past the coyote time, until landing again
+            self.airborne = Some(f32::INFINITY);
+            self.rising = true;
+            JumpAction::Start
+        } else if released && self.rising {
+            self.rising = false;
+            JumpAction::Cut
+        } else {
+            JumpAction::None
+        }
+    }
+
+    /// Apex reached, releasing the jump no longer cuts it
+    pub fn fall(&mut self) {
+        self.rising = false;
+    }
+}
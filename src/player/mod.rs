@@ -2,43 +2,64 @@
  * Copyright (c) 2024 Louis Mayencourt
  */
 
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
 
 pub mod controller;
+pub mod dash;
+pub mod ghost;
+pub mod jump;
 pub mod movement;
 pub mod sprites;
 
 use crate::{
-    physics::{Collider, RigidBody},
+    config::GameConfig,
+    daylight::Daylit,
+    headless::Headless,
+    physics::{self, Collider, CollisionLayers, RigidBody},
     ApplicationState, RestartEvent,
 };
 
 use controller::*;
+use dash::Dash;
+use jump::Jump;
 use sprites::*;
 
 pub const SPRITE_HEIGHT: u32 = 15;
 pub const SPRITE_WIDTH: u32 = 24;
 pub const SPRITE_IDLE_IDX: (usize, usize) = (0, 5);
 pub const SPRITE_RUN_IDX: (usize, usize) = (6, 11);
+pub const SPRITE_DASH_IDX: usize = 12;
+
+const PLAYER_START_POSITION: Vec2 = Vec2::new(0.0, 40.0);
 
 #[derive(Component)]
 pub struct Player {
-    state: PlayerState,
     pub attitude: PlayerAttitude,
 }
 
-#[derive(Debug)]
-enum PlayerState {
+/// Drive the sprite animation, for the player and its ghost
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum PlayerState {
     Idle,
     Running,
     Jumping,
+    Dashing,
+    Sliding,
     Dead,
 }
 
 #[derive(Debug)]
 pub enum PlayerAttitude {
     InAir,
+    /// Standing on the ground or on a platform
+    OnGround,
+    /// Sliding down the side of a wall, `normal` points away from it
+    OnWall {
+        normal: Vec2,
+    },
     InWall,
 }
 
@@ -48,22 +69,45 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(sprites::AnimationUpDown(true));
         app.add_systems(Startup, setup);
-        app.add_systems(Update, restart_event_handler);
+        // Reset before the first tick of the new run
+        app.add_systems(PreUpdate, restart_event_handler);
         app.add_systems(
             FixedUpdate,
-            controller::keyboard_inputs.run_if(in_state(ApplicationState::InGame)),
+            controller::keyboard_inputs
+                .run_if(in_state(ApplicationState::InGame))
+                .run_if(not(resource_exists::<Headless>)),
         );
         app.add_systems(
             FixedUpdate,
             movement::player_movement
                 .after(controller::keyboard_inputs)
+                .before(physics::bodies_movement)
                 .run_if(in_state(ApplicationState::InGame)),
         );
         app.add_systems(
             FixedUpdate,
-            movement::collide_event_handler.run_if(in_state(ApplicationState::InGame)),
+            movement::collide_event_handler
+                .after(physics::collision)
+                .run_if(in_state(ApplicationState::InGame)),
         );
         app.add_systems(Update, sprites::animate_sprite);
+        app.add_systems(Update, dash::follow_player);
+        app.add_systems(
+            PreUpdate,
+            apply_config.run_if(resource_changed::<GameConfig>),
+        );
+        // Ghost
+        app.init_resource::<ghost::BestRuns>();
+        app.init_resource::<ghost::TrajectoryRecorder>();
+        app.add_systems(PreUpdate, ghost::restart_event_handler);
+        app.add_systems(
+            FixedUpdate,
+            // Positions once the collisions are resolved
+            (ghost::record_trajectory, ghost::ghost_movement)
+                .after(physics::collision)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(OnEnter(ApplicationState::GameEnding), ghost::end_run);
     }
 }
 
@@ -71,15 +115,24 @@ fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    config: Res<GameConfig>,
 ) {
     let texture = asset_server.load("embedded://snow-fox-sprites.png");
     let layout =
-        TextureAtlasLayout::from_grid(UVec2::new(SPRITE_WIDTH, SPRITE_HEIGHT), 6, 2, None, None);
+        TextureAtlasLayout::from_grid(UVec2::new(SPRITE_WIDTH, SPRITE_HEIGHT), 6, 3, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
     let animation_indices = AnimationIndices { first: 0, last: 5 };
     let mut camera = Camera2dBundle::default();
-    camera.projection.scaling_mode = ScalingMode::FixedVertical(780.0);
+    camera.projection.scaling_mode = camera_scaling(&config);
     commands.spawn(camera);
+    let frame_time = config.player.animation_frame_time;
+    ghost::spawn_ghost(
+        &mut commands,
+        texture.clone(),
+        texture_atlas_layout.clone(),
+        frame_time,
+    );
+    dash::spawn_trail(&mut commands);
     commands.spawn((
         SpriteSheetBundle {
             texture,
@@ -87,33 +140,72 @@ fn setup(
                 layout: texture_atlas_layout,
                 index: animation_indices.first,
             },
-            transform: Transform::from_xyz(0.0, 40.0, 0.0).with_scale(Vec3::splat(4.0)),
+            transform: Transform::from_translation(PLAYER_START_POSITION.extend(0.0))
+                .with_scale(Vec3::splat(4.0)),
             ..default()
         },
         animation_indices,
-        AnimationTimer(Timer::from_seconds(0.1, TimerMode::Repeating)),
+        AnimationTimer(Timer::from_seconds(frame_time, TimerMode::Repeating)),
         Player {
-            state: PlayerState::Idle,
             attitude: PlayerAttitude::InAir,
             // jump_timer: Timer::from_seconds(0.4, TimerMode::Repeating),
         },
+        PlayerState::Idle,
+        Daylit::default(),
+        Dash::default(),
+        Jump::default(),
         Controller {
             direction: Vec2::ZERO,
             action: Action::None,
+            jump_held: false,
         },
-        Collider,
+        // Body of the fox, the 24x15 pixels sprite is scaled by 4
+        Collider::aabb(Vec2::new(32.0, 22.0))
+            .with_layers(
+                CollisionLayers::PLAYER,
+                CollisionLayers::OBSTACLE | CollisionLayers::WAYPOINT | CollisionLayers::GROUND,
+            )
+            .with_blocking(CollisionLayers::OBSTACLE | CollisionLayers::GROUND),
         RigidBody {
-            position: Vec2::new(0.0, 40.0),
+            position: PLAYER_START_POSITION,
             ..default()
         },
-        // ShowAabbGizmo { color: None },
     ));
 }
 
-pub fn restart_event_handler(events: EventReader<RestartEvent>, mut query: Query<&mut Player>) {
+/// Show the world between its borders
+fn camera_scaling(config: &GameConfig) -> ScalingMode {
+    ScalingMode::FixedVertical(config.world.height - config.obstacle.width)
+}
+
+/// Follow the configuration when it is reloaded
+fn apply_config(
+    config: Res<GameConfig>,
+    mut cameras: Query<&mut OrthographicProjection>,
+    mut timers: Query<&mut AnimationTimer>,
+) {
+    for mut projection in cameras.iter_mut() {
+        projection.scaling_mode = camera_scaling(&config);
+    }
+    for mut timer in timers.iter_mut() {
+        timer.set_duration(Duration::from_secs_f32(config.player.animation_frame_time));
+    }
+}
+
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut query: Query<(&mut Player, &mut RigidBody, &mut Dash, &mut Jump)>,
+) {
     if !events.is_empty() {
+        events.clear();
         info!("Restart Game!");
-        let mut player = query.single_mut();
+        let (mut player, mut body, mut dash, mut jump) = query.single_mut();
         player.attitude = PlayerAttitude::InAir;
+        *dash = Dash::default();
+        *jump = Jump::default();
+        *body = RigidBody {
+            position: PLAYER_START_POSITION,
+            ..default()
+        };
     }
 }
@@ -14,29 +14,112 @@
 
 use bevy::prelude::*;
 
-use crate::physics::{CollideEvent, CollideWith};
+use crate::config::{GameConfig, GameMode};
+use crate::physics::{CollideEvent, CollisionLayers};
+use crate::player::dash::Dash;
+use crate::player::jump::{Jump, JumpAction};
 use crate::player::*;
+use crate::world::Wind;
 
-pub fn player_movement(mut query: Query<(&mut RigidBody, &Controller, &mut Player)>) {
-    let (mut body, controller, mut player) = query.single_mut();
+/// Pull back to the start position, against the horizontal wind
+const HOLD_STIFFNESS: f32 = 9.0;
+const HOLD_DAMPING: f32 = 6.0;
+/// Horizontal acceleration toward the wall while sliding down its side
+const WALL_GRIP: f32 = 100.0;
 
-    debug!("Player state {:?}", player.state);
+pub fn player_movement(
+    mut query: Query<(
+        &mut RigidBody,
+        &Controller,
+        &mut Player,
+        &mut PlayerState,
+        &mut Dash,
+        &mut Jump,
+    )>,
+    config: Res<GameConfig>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    let (mut body, controller, mut player, mut state, mut dash, mut jump) = query.single_mut();
+
+    debug!("Player state {:?}", *state);
     debug!("Player attitude {:?}", player.attitude);
     debug!("Control state {:?}", controller.direction);
     match player.attitude {
-        PlayerAttitude::InAir => {
-            player.state = PlayerState::Running;
+        PlayerAttitude::InAir | PlayerAttitude::OnGround | PlayerAttitude::OnWall { .. } => {
+            // The collision puts the fox back on the surface while it touches it
+            let (grounded, wall) =
+                match std::mem::replace(&mut player.attitude, PlayerAttitude::InAir) {
+                    PlayerAttitude::OnGround => (true, None),
+                    PlayerAttitude::OnWall { normal } => (false, Some(normal)),
+                    _ => (false, None),
+                };
+            if grounded || wall.is_some() {
+                // Jumping off the surface, or shortly after leaving it
+                jump.land();
+            }
+            let dash_pressed = dash.pressed(controller.action == Action::Dash);
+            if dash_pressed && dash.available && !dash.is_dashing() {
+                dash.start(controller.direction);
+            }
+            // A jump pressed while dashing waits in the jump buffer
+            let running = config.mode == GameMode::Running;
+            let jump_action = jump.update(
+                controller.jump_held,
+                !dash.is_dashing(),
+                config.player.air_jump && !running,
+                time.delta_seconds(),
+                &config.player,
+            );
+            // Neither gravity nor wind while dashing
+            if dash.is_dashing() {
+                *state = PlayerState::Dashing;
+                dash.tick(&mut body);
+                return;
+            }
 
-            // Can only jump if on the ground
-            if controller.action == Action::Jump {
-                player.state = PlayerState::Jumping;
-                body.velocity.y = 450.0;
+            match jump_action {
+                JumpAction::Start => {
+                    body.velocity.y = if running {
+                        config.player.running_jump_velocity
+                    } else {
+                        config.player.jump_velocity
+                    };
+                    if let Some(normal) = wall {
+                        body.velocity.x = normal.x * config.player.wall_jump_push;
+                    }
+                }
+                // Released early, for a lower jump
+                JumpAction::Cut if body.velocity.y > 0.0 => {
+                    body.velocity.y *= config.player.jump_cut;
+                }
+                _ => {}
+            }
+            if body.velocity.y <= 0.0 {
+                jump.fall();
+            }
+            let sliding = wall.filter(|_| !jump.is_rising());
+            if sliding.is_some() {
+                body.velocity.y = body.velocity.y.max(-config.player.wall_slide_speed);
+            }
+            *state = if jump.is_rising() {
+                PlayerState::Jumping
+            } else if sliding.is_some() {
+                PlayerState::Sliding
+            } else {
+                PlayerState::Running
+            };
+            // when in air, gravity and wind apply
+            let drift = body.position.x - PLAYER_START_POSITION.x;
+            let hold = -HOLD_STIFFNESS * drift - HOLD_DAMPING * body.velocity.x;
+            body.acceleration = Vec2::new(hold, config.player.gravity) + wind.acceleration;
+            if let Some(normal) = sliding {
+                // Pressed against the wall, whatever the wind
+                body.acceleration.x = -normal.x * WALL_GRIP;
             }
-            // when in air, gravity applies
-            body.acceleration.y = -1500.0;
         }
         PlayerAttitude::InWall => {
-            player.state = PlayerState::Dead;
+            *state = PlayerState::Dead;
             body.velocity = Vec2::ZERO;
             body.acceleration = Vec2::ZERO;
             body.position.y = 0.0;
@@ -46,15 +129,59 @@ pub fn player_movement(mut query: Query<(&mut RigidBody, &Controller, &mut Playe
 
 pub fn collide_event_handler(
     mut events: EventReader<CollideEvent>,
-    mut query: Query<&mut Player>,
+    mut query: Query<(&mut Player, &mut Dash, &RigidBody)>,
+    obstacles: Query<&RigidBody, Without<Player>>,
+    config: Res<GameConfig>,
     mut next_state: ResMut<NextState<ApplicationState>>,
 ) {
     for event in events.read() {
-        if let CollideWith::Obstacle = event.other {
+        let Ok((mut player, mut dash, body)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if matches!(player.attitude, PlayerAttitude::InWall) {
+            continue;
+        }
+        if event
+            .other_layer
+            .intersects(CollisionLayers::OBSTACLE | CollisionLayers::GROUND)
+        {
+            // Land on the top of the ground, only the sides of the walls can
+            // be slid down, the other sides of the ground only block the fox
+            let on_top = event.normal.y > event.normal.x.abs();
+            let side = event.normal.x.abs() > event.normal.y.abs();
+            let ground = event.other_layer.intersects(CollisionLayers::GROUND);
+            let attitude = if ground && on_top {
+                Some(PlayerAttitude::OnGround)
+            } else if config.player.wall_slide && side {
+                Some(PlayerAttitude::OnWall {
+                    normal: event.normal,
+                })
+            } else {
+                None
+            };
+            if ground || attitude.is_some() {
+                let leaving = obstacles
+                    .get(event.other)
+                    .is_ok_and(|other| (body.velocity - other.velocity).dot(event.normal) > 0.0);
+                if leaving {
+                    // Jumping off the surface
+                    continue;
+                }
+                // The physics keeps the fox against the surface
+                if body.position.x >= config.world.left() {
+                    if let Some(attitude) = attitude {
+                        player.attitude = attitude;
+                    }
+                    continue;
+                }
+                info!("Pushed off the screen");
+            }
             info!("End of Game !");
-            let mut player = query.single_mut();
             player.attitude = PlayerAttitude::InWall;
             next_state.set(ApplicationState::GameEnding);
+        } else if event.other_layer.intersects(CollisionLayers::WAYPOINT) {
+            // Flying through a gap refills the dash
+            dash.available = true;
         }
     }
 }
@@ -26,15 +26,15 @@ pub fn animate_sprite(
         &AnimationIndices,
         &mut AnimationTimer,
         &mut TextureAtlas,
-        &mut Player,
+        &PlayerState,
         &mut Transform,
     )>,
     mut animation_up: ResMut<AnimationUpDown>,
 ) {
-    for (indices, mut timer, mut atlas, player, mut transform) in &mut query {
+    for (indices, mut timer, mut atlas, state, mut transform) in &mut query {
         timer.tick(time.delta());
 
-        match player.state {
+        match state {
             PlayerState::Idle => {
                 transform.rotation = Quat::from_rotation_x(0.0);
                 if timer.just_finished() {
@@ -70,6 +70,12 @@ pub fn animate_sprite(
             PlayerState::Jumping => {
                 atlas.index = 9;
             }
+            PlayerState::Dashing => {
+                atlas.index = SPRITE_DASH_IDX;
+            }
+            PlayerState::Sliding => {
+                atlas.index = SPRITE_IDLE_IDX.0;
+            }
             PlayerState::Dead => {
                 atlas.index = 6;
                 transform.rotation = Quat::from_rotation_x(std::f32::consts::PI);
@@ -0,0 +1,277 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Record the inputs of a run and replay them
+///
+/// A run is fully defined by its world seed and the controller action and
+/// direction of every fixed tick. Start the game with `--record <file>` to save the last
+/// run, and with `--replay <file>` to play a saved run back.
+use bevy::prelude::*;
+
+use crate::player::controller::{self, Action, Controller};
+use crate::player::movement;
+use crate::world::WorldSeed;
+use crate::{command_line_arg, ApplicationState, RestartEvent};
+
+const FILE_MAGIC: &[u8; 4] = b"FLYN";
+/// Bumped whenever a run plays differently from the same inputs, older files
+/// would desync. Version 2 adds the direction, version 3 draws the world from
+/// ChaCha8, version 4 holds the jump apart from the dash, and covers the jumps
+/// on the press, the running mode and the collisions resolved by the physics,
+/// version 5 dashes on the press, version 6 closes the gates.
+const FILE_VERSION: u8 = 6;
+/// Longest run a file can hold, four hours at 64 ticks per second, so a
+/// malformed file can't allocate without bound
+const MAX_TICKS: usize = 4 * 60 * 60 * 64;
+
+/// Controller state of a fixed tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TickInput {
+    pub action: Action,
+    pub jump_held: bool,
+    /// Direction held, each axis being -1, 0 or 1
+    pub direction: IVec2,
+}
+
+impl TickInput {
+    pub fn from_controller(controller: &Controller) -> Self {
+        let axis = |value: f32| {
+            if value > 0.5 {
+                1
+            } else if value < -0.5 {
+                -1
+            } else {
+                0
+            }
+        };
+        TickInput {
+            action: controller.action,
+            jump_held: controller.jump_held,
+            direction: IVec2::new(axis(controller.direction.x), axis(controller.direction.y)),
+        }
+    }
+}
+
+/// Seed and per-tick inputs of a run
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InputRecording {
+    pub seed: u64,
+    pub inputs: Vec<TickInput>,
+}
+
+impl InputRecording {
+    /// Encode the recording, with the inputs run-length encoded
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(FILE_MAGIC);
+        bytes.push(FILE_VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+
+        let mut inputs = self.inputs.iter().peekable();
+        while let Some(input) = inputs.next() {
+            let mut count: u32 = 1;
+            while inputs.next_if_eq(&input).is_some() {
+                count += 1;
+            }
+            bytes.push(input_to_byte(input));
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.strip_prefix(FILE_MAGIC)?;
+        let (version, bytes) = bytes.split_first()?;
+        if *version != FILE_VERSION || bytes.len() < 8 {
+            return None;
+        }
+        let (seed, mut bytes) = bytes.split_at(8);
+        let mut recording = InputRecording {
+            seed: u64::from_le_bytes(seed.try_into().ok()?),
+            inputs: Vec::new(),
+        };
+
+        while !bytes.is_empty() {
+            if bytes.len() < 5 {
+                return None;
+            }
+            let input = byte_to_input(bytes[0])?;
+            let count = u32::from_le_bytes(bytes[1..5].try_into().ok()?) as usize;
+            if recording.inputs.len() + count > MAX_TICKS {
+                return None;
+            }
+            recording.inputs.extend(std::iter::repeat_n(input, count));
+            bytes = &bytes[5..];
+        }
+        Some(recording)
+    }
+}
+
+/// Jump held in the lowest bit, then the dash, then two bits for each
+/// direction axis
+fn input_to_byte(input: &TickInput) -> u8 {
+    let dash = match input.action {
+        Action::None => 0,
+        Action::Dash => 1,
+    };
+    u8::from(input.jump_held)
+        | (dash << 1)
+        | (axis_to_bits(input.direction.x) << 2)
+        | (axis_to_bits(input.direction.y) << 4)
+}
+
+fn byte_to_input(byte: u8) -> Option<TickInput> {
+    if byte >> 6 != 0 {
+        return None;
+    }
+    let action = if byte & 0b10 != 0 {
+        Action::Dash
+    } else {
+        Action::None
+    };
+    Some(TickInput {
+        action,
+        jump_held: byte & 0b1 != 0,
+        direction: IVec2::new(bits_to_axis(byte >> 2)?, bits_to_axis(byte >> 4)?),
+    })
+}
+
+fn axis_to_bits(axis: i32) -> u8 {
+    match axis {
+        0 => 0,
+        1 => 1,
+        _ => 2,
+    }
+}
+
+fn bits_to_axis(bits: u8) -> Option<i32> {
+    match bits & 0b11 {
+        0 => Some(0),
+        1 => Some(1),
+        2 => Some(-1),
+        _ => None,
+    }
+}
+
+/// Recording of the current run
+#[derive(Resource, Default)]
+pub struct Recorder {
+    pub recording: InputRecording,
+    /// Where to save the recording when the run ends
+    pub path: Option<String>,
+}
+
+/// Replace the controller inputs with a recorded run
+#[derive(Resource)]
+pub struct Replay {
+    pub recording: InputRecording,
+    tick: usize,
+}
+
+impl Replay {
+    pub fn new(recording: InputRecording) -> Self {
+        Replay { recording, tick: 0 }
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Recorder {
+            path: command_line_arg("--record"),
+            ..default()
+        });
+
+        if let Some(path) = command_line_arg("--replay") {
+            match std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| InputRecording::from_bytes(&bytes))
+            {
+                Some(recording) => {
+                    info!("Replay {} with seed {}", path, recording.seed);
+                    app.insert_resource(WorldSeed::locked(recording.seed));
+                    app.insert_resource(Replay::new(recording));
+                }
+                None => error!("Can't read replay file {}", path),
+            }
+        }
+
+        app.add_systems(PreUpdate, restart_event_handler);
+        app.add_systems(
+            FixedUpdate,
+            (
+                replay_inputs
+                    .after(controller::keyboard_inputs)
+                    .run_if(resource_exists::<Replay>),
+                record_inputs,
+            )
+                .chain()
+                .before(movement::player_movement)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(OnEnter(ApplicationState::GameEnding), save_recording);
+    }
+}
+
+fn replay_inputs(mut replay: ResMut<Replay>, mut query: Query<&mut Controller>) {
+    let mut controller = query.single_mut();
+
+    let input = match replay.recording.inputs.get(replay.tick) {
+        Some(input) => *input,
+        None => {
+            if replay.tick == replay.recording.inputs.len() {
+                info!("End of replay");
+            }
+            TickInput {
+                action: Action::None,
+                jump_held: false,
+                direction: IVec2::ZERO,
+            }
+        }
+    };
+    controller.action = input.action;
+    controller.jump_held = input.jump_held;
+    controller.direction = input.direction.as_vec2();
+    replay.tick += 1;
+}
+
+fn record_inputs(mut recorder: ResMut<Recorder>, seed: Res<WorldSeed>, query: Query<&Controller>) {
+    let controller = query.single();
+    recorder.recording.seed = seed.value;
+    recorder
+        .recording
+        .inputs
+        .push(TickInput::from_controller(controller));
+}
+
+fn save_recording(recorder: Res<Recorder>) {
+    let Some(path) = &recorder.path else {
+        return;
+    };
+
+    info!(
+        "Save run of {} ticks to {}",
+        recorder.recording.inputs.len(),
+        path
+    );
+    if let Err(err) = std::fs::write(path, recorder.recording.to_bytes()) {
+        error!("Can't save recording to {}: {}", path, err);
+    }
+}
+
+/// Start recording, and replaying, the new run from its first tick
+fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut recorder: ResMut<Recorder>,
+    replay: Option<ResMut<Replay>>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        recorder.recording.inputs.clear();
+        if let Some(mut replay) = replay {
+            replay.tick = 0;
+        }
+    }
+}
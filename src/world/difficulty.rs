@@ -0,0 +1,119 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::config::{positive, GameConfig};
+use crate::RestartEvent;
+
+use super::ScoreBoard;
+
+/// Obstacle settings at a given point of the run
+#[derive(Resource, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub obstacle_speed: f32,
+    /// Seconds between two obstacles
+    pub spawn_interval: f32,
+    pub gap_size: f32,
+}
+
+impl Difficulty {
+    fn lerp(&self, other: &Difficulty, t: f32) -> Difficulty {
+        Difficulty {
+            obstacle_speed: self.obstacle_speed.lerp(other.obstacle_speed, t),
+            spawn_interval: self.spawn_interval.lerp(other.spawn_interval, t),
+            gap_size: self.gap_size.lerp(other.gap_size, t),
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        positive(&format!("{}.obstacle_speed", name), self.obstacle_speed)?;
+        positive(&format!("{}.spawn_interval", name), self.spawn_interval)?;
+        positive(&format!("{}.gap_size", name), self.gap_size)
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        DifficultyCurve::default().easiest
+    }
+}
+
+/// Ramp from the `easiest` to the `hardest` difficulty, reached at
+/// `max_score` or after `max_run_time` seconds, whichever comes first
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct DifficultyCurve {
+    pub easiest: Difficulty,
+    pub hardest: Difficulty,
+    pub max_score: u32,
+    pub max_run_time: f32,
+    /// Shape of the ramp: 1 is linear, above 1 starts slowly, below 1 starts fast
+    pub exponent: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve {
+            easiest: Difficulty {
+                obstacle_speed: 120.0,
+                spawn_interval: 2.0,
+                gap_size: 200.0,
+            },
+            hardest: Difficulty {
+                obstacle_speed: 240.0,
+                spawn_interval: 1.2,
+                gap_size: 140.0,
+            },
+            max_score: 50,
+            max_run_time: 180.0,
+            exponent: 1.5,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub fn validate(&self) -> Result<(), String> {
+        self.easiest.validate("difficulty.easiest")?;
+        self.hardest.validate("difficulty.hardest")?;
+        if self.max_score == 0 {
+            return Err("difficulty.max_score is zero".to_string());
+        }
+        positive("difficulty.max_run_time", self.max_run_time)?;
+        positive("difficulty.exponent", self.exponent)
+    }
+
+    /// Position on the curve, from 0 at the easiest to 1 at the hardest
+    pub fn level(&self, score: u32, run_time: f32) -> f32 {
+        let progress = (score as f32 / self.max_score as f32)
+            .max(run_time / self.max_run_time)
+            .clamp(0.0, 1.0);
+        progress.powf(self.exponent)
+    }
+
+    pub fn at(&self, score: u32, run_time: f32) -> Difficulty {
+        self.easiest
+            .lerp(&self.hardest, self.level(score, run_time))
+    }
+}
+
+pub fn update_difficulty(
+    config: Res<GameConfig>,
+    scorebard: Res<ScoreBoard>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    *difficulty = config.difficulty.at(scorebard.score, scorebard.run_time);
+}
+
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    config: Res<GameConfig>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        *difficulty = config.difficulty.easiest;
+    }
+}
@@ -2,40 +2,55 @@
  * Copyright (c) 2024 Louis Mayencourt
  */
 
+use std::time::Duration;
+
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{
-    physics::{CollideEvent, CollideWith, Collider, RigidBody},
+    config::{GameConfig, GameMode},
+    daylight::Daylit,
+    physics::{self, CollideEvent, Collider, CollisionLayers, RigidBody},
+    player::{movement, Player},
     ApplicationState, RestartEvent,
 };
 
+mod difficulty;
+mod motion;
+mod parallax;
+mod pattern;
+mod pool;
+mod seed;
+mod terrain;
+mod theme;
+mod weather;
 mod wind;
 
-/// World size definition
-const WORLD_HEIGHT: f32 = 800.0;
-const WORLD_TOP: f32 = WORLD_HEIGHT / 2.0;
-const WORLD_BOTTOM: f32 = -WORLD_TOP;
-// Use the golden ration here for the world size
-const WORLD_WIDTH: f32 = WORLD_HEIGHT * 1.618;
-const WORLD_RIGHT: f32 = WORLD_WIDTH / 2.0;
-const WORLD_LEFT: f32 = -WORLD_RIGHT;
-
-// Obstacle constant
-const OBSTACLE_GAP_SIZE: f32 = 200.0;
-const OBSTACLE_WIDTH: f32 = 20.0;
-const OBSTACLE_SPEED: f32 = 120.0;
-
-const OBSTACLE_SPAWN_SPEED: f32 = 2.0;
-const OBSTACLE_DESPAWN_SPEED: f32 = 0.20;
-
-// const OBSTACLE_
+pub use difficulty::{Difficulty, DifficultyCurve};
+pub use motion::{Charge, Gate, Motion, Oscillation};
+pub use parallax::ParallaxLayer;
+pub use pattern::{choose_pattern, default_patterns, Column, Gap, ObstaclePattern};
+pub use pool::{ObstaclePool, Pooled};
+pub use seed::{WorldRng, WorldSeed};
+pub use terrain::{Ground, Platform, MIN_GROUND_LENGTH};
+pub use theme::{ObstacleTextures, ObstacleTheme};
+pub use weather::Weather;
+pub use wind::Wind;
 
 #[derive(Component)]
 pub struct Obstacle;
 
+#[derive(Component)]
+enum Border {
+    Top,
+    Bottom,
+}
+
 #[derive(Resource)]
 struct ObstacleSpawnTimer {
     timer: Timer,
+    /// Time for the last spawned pattern to pass its first column
+    pattern_delay: f32,
 }
 
 #[derive(Resource)]
@@ -44,8 +59,10 @@ struct ObstacleDespawnTimer {
 }
 
 #[derive(Resource)]
-struct ScoreBoard {
-    score: u32,
+pub struct ScoreBoard {
+    pub score: u32,
+    /// Duration of the run in seconds
+    pub run_time: f32,
 }
 
 #[derive(Component)]
@@ -59,67 +76,157 @@ pub struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(ObstacleSpawnTimer {
-            timer: Timer::from_seconds(OBSTACLE_SPAWN_SPEED, TimerMode::Repeating),
+            timer: Timer::from_seconds(Difficulty::default().spawn_interval, TimerMode::Repeating),
+            pattern_delay: 0.0,
         });
         app.insert_resource(ObstacleDespawnTimer {
-            timer: Timer::from_seconds(OBSTACLE_DESPAWN_SPEED, TimerMode::Repeating),
+            timer: Timer::from_seconds(
+                GameConfig::default().obstacle.despawn_interval,
+                TimerMode::Repeating,
+            ),
         });
-        app.insert_resource(ScoreBoard { score: 0 });
-        app.add_systems(Startup, setup_world);
+        app.insert_resource(ScoreBoard {
+            score: 0,
+            run_time: 0.0,
+        });
+        app.init_resource::<WorldSeed>();
+        app.init_resource::<Difficulty>();
+        app.init_resource::<ObstaclePool>();
+        app.add_systems(
+            Startup,
+            (
+                setup_world,
+                seed::setup_rng,
+                pool::setup_pool,
+                theme::setup_textures,
+            ),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (
+                collide_event_handler,
+                difficulty::update_difficulty,
+                update_world,
+                terrain::extend_ground.run_if(terrain::running_mode),
+            )
+                .chain()
+                .after(physics::collision)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (motion::oscillate, motion::open_close_gates, motion::charge)
+                .before(physics::bodies_movement)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
         app.add_systems(
             Update,
-            update_world.run_if(in_state(ApplicationState::InGame)),
+            update_scoreboard.run_if(in_state(ApplicationState::InGame)),
         );
         app.add_systems(
             Update,
             clear_world.run_if(in_state(ApplicationState::GameEnding)),
         );
+        // Reset before the first tick of the new run
         app.add_systems(
-            Update,
-            collide_event_handler.run_if(in_state(ApplicationState::InGame)),
+            PreUpdate,
+            (
+                restart_event_handler,
+                seed::restart_event_handler,
+                difficulty::restart_event_handler,
+                wind::restart_event_handler,
+                weather::restart_event_handler,
+            ),
         );
         app.add_systems(
-            Update,
-            restart_event_handler.run_if(in_state(ApplicationState::GameEnd)),
+            PreUpdate,
+            (
+                resize_world,
+                wind::resize_wind,
+                weather::resize_weather,
+                parallax::resize_layers,
+            )
+                .run_if(resource_changed::<GameConfig>),
         );
         // Wind
+        app.init_resource::<Wind>();
+        app.add_systems(
+            PreUpdate,
+            wind::reset_wind
+                .run_if(in_state(ApplicationState::LandingScreen))
+                .run_if(resource_changed::<GameConfig>),
+        );
         app.add_systems(Startup, wind::spawn_particle_system);
+        app.add_systems(
+            FixedUpdate,
+            wind::update_wind
+                .before(movement::player_movement)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            wind::drive_particles.run_if(resource_changed::<Wind>),
+        );
+        // Weather
+        app.init_resource::<Weather>();
+        app.init_resource::<weather::WeatherSpell>();
+        app.add_systems(
+            PreUpdate,
+            weather::reset_spell
+                .run_if(in_state(ApplicationState::LandingScreen))
+                .run_if(resource_changed::<GameConfig>),
+        );
+        app.add_systems(Startup, weather::spawn_weather);
+        app.add_systems(
+            FixedUpdate,
+            weather::update_weather
+                .after(terrain::extend_ground)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+        app.add_systems(
+            Update,
+            weather::drive_snowfall
+                .run_if(resource_changed::<Weather>.or_else(resource_changed::<Wind>)),
+        );
+        app.add_systems(Update, weather::update_fog);
+        // Background
+        app.add_systems(Startup, parallax::spawn_layers);
+        app.add_systems(
+            FixedUpdate,
+            parallax::scroll_layers.run_if(in_state(ApplicationState::InGame)),
+        );
     }
 }
 
-fn setup_world(mut commands: Commands) {
+fn setup_world(mut commands: Commands, config: Res<GameConfig>) {
     // Top world border
     commands.spawn((
         SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(WORLD_LEFT + WORLD_WIDTH / 2.0, WORLD_TOP, 0.0),
-                scale: Vec3::new(WORLD_WIDTH, OBSTACLE_WIDTH, 0.0),
-                ..default()
-            },
+            transform: border_transform(&config, &Border::Top),
             sprite: Sprite {
                 color: Color::rgb(1.0, 1.0, 1.0),
                 ..default()
             },
             ..default()
         },
-        Collider,
+        Border::Top,
+        border_collider(&config),
+        Daylit::default(),
     ));
 
     // Bottom world border
     commands.spawn((
         SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(WORLD_LEFT + WORLD_WIDTH / 2.0, WORLD_BOTTOM, 0.0),
-                scale: Vec3::new(WORLD_WIDTH, OBSTACLE_WIDTH, 0.0),
-                ..default()
-            },
+            transform: border_transform(&config, &Border::Bottom),
             sprite: Sprite {
                 color: Color::rgb(1.0, 1.0, 1.0),
                 ..default()
             },
             ..default()
         },
-        Collider,
+        Border::Bottom,
+        border_collider(&config),
+        Daylit::default(),
     ));
 
     // Spawn Scoreboard
@@ -129,42 +236,125 @@ fn setup_world(mut commands: Commands) {
                 TextSection::new("Score: ", TextStyle { ..default() }),
                 TextSection::from_style(TextStyle { ..default() }),
             ]),
-            transform: Transform {
-                translation: Vec3::new(WORLD_LEFT + 80.0, WORLD_TOP - 80.0, 0.0),
-                ..default()
-            },
+            transform: Transform::from_translation(scoreboard_position(&config)),
             ..default()
         },
         ScoreBoardUi,
     ));
 }
 
+fn border_transform(config: &GameConfig, border: &Border) -> Transform {
+    let y = match border {
+        Border::Top => config.world.top(),
+        Border::Bottom => config.world.bottom(),
+    };
+    Transform {
+        translation: Vec3::new(0.0, y, 0.0),
+        scale: Vec3::new(config.world.width(), config.obstacle.width, 0.0),
+        ..default()
+    }
+}
+
+fn border_collider(config: &GameConfig) -> Collider {
+    Collider::aabb(Vec2::new(config.world.width(), config.obstacle.width) / 2.0)
+        .with_layers(CollisionLayers::OBSTACLE, CollisionLayers::NONE)
+}
+
+fn scoreboard_position(config: &GameConfig) -> Vec3 {
+    Vec3::new(config.world.left() + 80.0, config.world.top() - 80.0, 0.0)
+}
+
+/// Follow the world size when the configuration is reloaded
+fn resize_world(
+    config: Res<GameConfig>,
+    mut borders: Query<(&Border, &mut Transform, &mut Collider)>,
+    mut scoreboard: Query<&mut Transform, (With<ScoreBoardUi>, Without<Border>)>,
+) {
+    for (border, mut transform, mut collider) in borders.iter_mut() {
+        *transform = border_transform(&config, border);
+        *collider = border_collider(&config);
+    }
+    scoreboard.single_mut().translation = scoreboard_position(&config);
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update_world(
     mut commands: Commands,
-    mut obstacles_query: Query<(&Transform, Entity), With<Obstacle>>,
-    scorebard: ResMut<ScoreBoard>,
-    mut query: Query<&mut Text, With<ScoreBoardUi>>,
+    mut obstacles_query: Query<
+        (&Collider, &mut RigidBody, Option<&Charge>, Entity),
+        With<Obstacle>,
+    >,
     mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+    mut pool: ResMut<ObstaclePool>,
+    textures: Res<ObstacleTextures>,
+    mut rng: ResMut<WorldRng>,
+    mut scorebard: ResMut<ScoreBoard>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
-    for (transform, entity) in obstacles_query.iter_mut() {
-        if transform.translation.x < WORLD_LEFT {
+    scorebard.run_time += time.delta_seconds();
+
+    for (collider, mut body, charge, entity) in obstacles_query.iter_mut() {
+        // Once entirely out of the world, for the long pieces of ground
+        if body.position.x + collider.shape.half_extents().x < config.world.left() {
             info!("Remove wall");
-            commands.entity(entity).despawn();
+            pool.release(&mut commands, entity);
+        }
+        // Every obstacle speeds up together, keeping the spacing between them
+        if charge.is_none() {
+            body.velocity.x = -difficulty.obstacle_speed;
         }
     }
 
+    // The ground of the running mode is extended by the terrain instead
+    if config.mode == GameMode::Running {
+        return;
+    }
+
+    // Leave the time for the previous pattern to pass before the next one
+    let interval = difficulty.spawn_interval + spawn_timer.pattern_delay;
+    spawn_timer
+        .timer
+        .set_duration(Duration::from_secs_f32(interval));
     spawn_timer.timer.tick(time.delta());
     if spawn_timer.timer.finished() {
-        let mut gap_pos: f32 = rand::random::<f32>() * WORLD_HEIGHT;
-        if gap_pos < OBSTACLE_GAP_SIZE {
-            gap_pos = OBSTACLE_GAP_SIZE;
-        } else if gap_pos > WORLD_HEIGHT - OBSTACLE_GAP_SIZE {
-            gap_pos = WORLD_HEIGHT - OBSTACLE_GAP_SIZE;
-        }
-        spawn_obstacle(&mut commands, gap_pos);
+        let level = config.difficulty.level(scorebard.score, scorebard.run_time);
+        let Some(pattern) = pattern::choose_pattern(&config.patterns, level, &mut rng.0) else {
+            warn!("No obstacle pattern available at level {}", level);
+            return;
+        };
+        let pattern = if rng.gen_bool(0.5) {
+            pattern.mirrored()
+        } else {
+            pattern.clone()
+        };
+
+        // Keep every gap at least a gap size away from the world edges
+        let (lowest_gap, highest_gap) = pattern.gap_range();
+        let margin = difficulty.gap_size + pattern.gap_travel();
+        let lowest = config.world.bottom() + margin - lowest_gap;
+        let highest = config.world.top() - margin - highest_gap;
+        let anchor = if lowest < highest {
+            rng.gen_range(lowest..=highest)
+        } else {
+            (lowest + highest) / 2.0
+        };
+        let theme = ObstacleTheme::random(&mut rng.0);
+        spawn_pattern(
+            &mut commands,
+            &mut pool,
+            (&textures, theme),
+            &pattern,
+            anchor,
+            &difficulty,
+            &config,
+        );
+        spawn_timer.pattern_delay = pattern.length() / difficulty.obstacle_speed;
     }
+}
 
+fn update_scoreboard(scorebard: Res<ScoreBoard>, mut query: Query<&mut Text, With<ScoreBoardUi>>) {
     let mut text = query.single_mut();
     text.sections[1].value = scorebard.score.to_string();
 }
@@ -173,13 +363,18 @@ fn clear_world(
     mut commands: Commands,
     query: Query<Entity, With<Obstacle>>,
     mut despawn_timer: ResMut<ObstacleDespawnTimer>,
+    mut pool: ResMut<ObstaclePool>,
     mut next_state: ResMut<NextState<ApplicationState>>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
+    despawn_timer
+        .timer
+        .set_duration(Duration::from_secs_f32(config.obstacle.despawn_interval));
     despawn_timer.timer.tick(time.delta());
     if despawn_timer.timer.finished() {
         if let Some(entity) = query.iter().next() {
-            commands.entity(entity).despawn();
+            pool.release(&mut commands, entity);
         }
     }
 
@@ -188,98 +383,195 @@ fn clear_world(
     }
 }
 
-fn spawn_obstacle(commands: &mut Commands, gap_position: f32) {
-    // Obstacles are composed of two walls, with a gap somewhere
-    let top_wall_height: f32 = WORLD_HEIGHT - (gap_position + OBSTACLE_GAP_SIZE / 2.0);
-    let top_wall_y_pos = WORLD_TOP - top_wall_height / 2.0;
-    let bottom_wall_height: f32 = WORLD_HEIGHT - top_wall_height - OBSTACLE_GAP_SIZE;
-    let bottom_wall_y_pos = WORLD_BOTTOM + bottom_wall_height / 2.0;
+fn spawn_pattern(
+    commands: &mut Commands,
+    pool: &mut ObstaclePool,
+    look: (&ObstacleTextures, ObstacleTheme),
+    pattern: &ObstaclePattern,
+    anchor: f32,
+    difficulty: &Difficulty,
+    config: &GameConfig,
+) {
+    let world = &config.world;
+    info!("Spawn {} pattern at {}", pattern.name, anchor);
 
-    info!("top height {} at {}", top_wall_height, top_wall_y_pos);
-    info!("bottom {} at {}", bottom_wall_height, bottom_wall_y_pos);
+    for column in pattern.columns.iter() {
+        let x = world.right() + column.offset;
+        let mut gaps: Vec<(f32, f32)> = column
+            .gaps
+            .iter()
+            .map(|gap| (anchor + gap.position, gap.size * difficulty.gap_size))
+            .collect();
+        gaps.sort_by(|a, b| a.0.total_cmp(&b.0));
 
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(WORLD_RIGHT, top_wall_y_pos, 0.0),
-                scale: Vec3::new(OBSTACLE_WIDTH, top_wall_height, 0.0),
-                ..default()
-            },
-            sprite: Sprite {
-                color: Color::rgb(1.0, 1.0, 1.0),
-                ..default()
-            },
-            ..default()
-        },
-        Obstacle,
-        Collider,
-        RigidBody {
-            // Constant speed, no gravity
-            position: Vec2::new(WORLD_RIGHT, top_wall_y_pos),
-            velocity: Vec2::new(-OBSTACLE_SPEED, 0.0),
-            ..Default::default()
-        },
-    ));
+        let velocity = Vec2::new(-difficulty.obstacle_speed, 0.0);
+        // Extend the walls past the world edges, to never open while moving
+        let widest_gap = gaps.iter().map(|&(_, size)| size).fold(0.0, f32::max);
+        let extension = match column.motion {
+            Some(Motion::Oscillate { amplitude, .. }) => amplitude,
+            Some(Motion::Gate { travel, .. }) => travel * widest_gap / 2.0,
+            _ => 0.0,
+        };
 
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(WORLD_RIGHT, bottom_wall_y_pos, 0.0),
-                scale: Vec3::new(OBSTACLE_WIDTH, bottom_wall_height, 0.0),
-                ..default()
-            },
-            sprite: Sprite {
-                color: Color::rgb(1.0, 1.0, 1.0),
-                ..default()
-            },
-            ..default()
-        },
+        // Obstacles are composed of walls, with gaps in between
+        let mut parts = Vec::new();
+        let mut wall_bottom = world.bottom() - extension;
+        for &(center, size) in gaps.iter() {
+            let wall_top = center - size / 2.0;
+            parts.extend(spawn_wall(
+                commands,
+                pool,
+                look,
+                x,
+                wall_bottom,
+                wall_top,
+                velocity,
+                config,
+            ));
+            let waypoint =
+                spawn_waypoint(commands, pool, Vec2::new(x, center), size, velocity, config);
+            parts.push(waypoint);
+            wall_bottom = center + size / 2.0;
+        }
+        let wall_top = world.top() + extension;
+        parts.extend(spawn_wall(
+            commands,
+            pool,
+            look,
+            x,
+            wall_bottom,
+            wall_top,
+            velocity,
+            config,
+        ));
+
+        match column.motion {
+            Some(Motion::Oscillate { amplitude, period }) => {
+                for &entity in parts.iter() {
+                    commands.entity(entity).insert(Oscillation {
+                        amplitude,
+                        period,
+                        age: 0.0,
+                    });
+                }
+            }
+            Some(Motion::Gate { travel, period }) => {
+                // The outer walls close the gaps, parts go from bottom to top
+                let lowest_gap = gaps.first().map_or(0.0, |&(_, size)| size);
+                let highest_gap = gaps.last().map_or(0.0, |&(_, size)| size);
+                let walls = Gate::outer_walls(travel, period, lowest_gap, highest_gap);
+                for (entity, gate) in [parts.first(), parts.last()].into_iter().zip(walls) {
+                    if let Some(&entity) = entity {
+                        commands.entity(entity).insert(gate);
+                    }
+                }
+            }
+            Some(Motion::Charge { acceleration }) => {
+                for &entity in parts.iter() {
+                    commands.entity(entity).insert(Charge { acceleration });
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_wall(
+    commands: &mut Commands,
+    pool: &mut ObstaclePool,
+    (textures, theme): (&ObstacleTextures, ObstacleTheme),
+    x: f32,
+    bottom: f32,
+    top: f32,
+    velocity: Vec2,
+    config: &GameConfig,
+) -> Option<Entity> {
+    if top <= bottom {
+        return None;
+    }
+    let width = config.obstacle.width;
+    let position = Vec2::new(x, (bottom + top) / 2.0);
+    let height = top - bottom;
+
+    let wall = pool.acquire(commands);
+    commands.entity(wall).insert((
+        Transform::from_translation(position.extend(0.0)),
+        textures.wall(theme, Vec2::new(config.obstacle.sprite_width, height)),
+        Visibility::Inherited,
         Obstacle,
-        Collider,
+        Collider::aabb(Vec2::new(width, height) / 2.0)
+            .with_layers(CollisionLayers::OBSTACLE, CollisionLayers::NONE),
         RigidBody {
             // Constant speed, no gravity
-            position: Vec2::new(WORLD_RIGHT, bottom_wall_y_pos),
-            velocity: Vec2::new(-OBSTACLE_SPEED, 0.0),
+            position,
+            velocity,
             ..Default::default()
         },
     ));
+    Some(wall)
+}
 
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(WORLD_RIGHT, WORLD_BOTTOM + gap_position, 0.0),
-                scale: Vec3::new(OBSTACLE_WIDTH, OBSTACLE_GAP_SIZE, 0.0),
-                ..default()
-            },
-            sprite: Sprite {
-                color: Color::rgba(0.75, 0.75, 0.75, 0.5),
-                ..default()
-            },
-            ..default()
-        },
+fn spawn_waypoint(
+    commands: &mut Commands,
+    pool: &mut ObstaclePool,
+    position: Vec2,
+    size: f32,
+    velocity: Vec2,
+    config: &GameConfig,
+) -> Entity {
+    let width = config.obstacle.width;
+    let waypoint = pool.acquire(commands);
+    commands.entity(waypoint).insert((
+        Transform::from_translation(position.extend(0.0)),
+        // Only drawn by the debug overlay
+        Visibility::Hidden,
         RigidBody {
             // Constant speed, no gravity
-            position: Vec2::new(WORLD_RIGHT, WORLD_BOTTOM + gap_position),
-            velocity: Vec2::new(-OBSTACLE_SPEED, 0.0),
+            position,
+            velocity,
             ..Default::default()
         },
+        Obstacle,
         Waypoint,
+        Collider::aabb(Vec2::new(width, size) / 2.0)
+            .with_layers(CollisionLayers::WAYPOINT, CollisionLayers::NONE),
     ));
+    waypoint
 }
 
 fn collide_event_handler(
     mut events: EventReader<CollideEvent>,
+    players: Query<(), With<Player>>,
     mut scorebard: ResMut<ScoreBoard>,
+    mut pool: ResMut<ObstaclePool>,
     mut commands: Commands,
 ) {
     for event in events.read() {
-        if let CollideWith::Waypoint(entity) = event.other {
+        // Only the fox scores through the gaps
+        if event.other_layer.intersects(CollisionLayers::WAYPOINT) && players.contains(event.entity)
+        {
             scorebard.score += 1;
-            commands.entity(entity).despawn();
+            pool.release(&mut commands, event.other);
         }
     }
 }
 
-fn restart_event_handler(_events: EventReader<RestartEvent>, mut scorebard: ResMut<ScoreBoard>) {
-    scorebard.score = 0;
+fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut scorebard: ResMut<ScoreBoard>,
+    query: Query<Entity, With<Obstacle>>,
+    mut pool: ResMut<ObstaclePool>,
+    mut commands: Commands,
+) {
+    if !events.is_empty() {
+        events.clear();
+        scorebard.score = 0;
+        scorebard.run_time = 0.0;
+
+        // Left over when restarting from the pause menu
+        for entity in query.iter() {
+            pool.release(&mut commands, entity);
+        }
+    }
 }
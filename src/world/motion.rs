@@ -0,0 +1,163 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Obstacle behaviours, driving the `RigidBody` before it is integrated
+///
+/// Every part of a column gets the same behaviour, so the walls and the
+/// waypoint keep moving together.
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::physics::RigidBody;
+use crate::player::Player;
+
+/// Time for a gate to open or to close
+const GATE_TRANSITION: f32 = 0.25;
+
+/// Behaviour of a column of a pattern
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Motion {
+    /// Move the whole column up and down
+    Oscillate { amplitude: f32, period: f32 },
+    /// Close the gaps by sliding the outer walls by `travel` times half of
+    /// their gap, then reopen them, a `travel` of 1 closes a single gap
+    Gate { travel: f32, period: f32 },
+    /// Speed up toward the player until passing it
+    Charge { acceleration: f32 },
+}
+
+impl Motion {
+    /// Highest vertical distance the gaps move away from their spawn position
+    pub fn gap_travel(&self) -> f32 {
+        match *self {
+            Motion::Oscillate { amplitude, .. } => amplitude,
+            _ => 0.0,
+        }
+    }
+
+    /// The oscillations and the gates repeat over a positive period, the
+    /// gates close at most up to the neighbour walls
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Motion::Oscillate { period, .. } | Motion::Gate { period, .. } if period <= 0.0 => {
+                Err(format!("Motion period {} is not positive", period))
+            }
+            Motion::Gate { travel, .. } if !(0.0..=1.0).contains(&travel) => {
+                Err(format!("Gate travel {} is not between 0 and 1", travel))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Vertical sine motion around the spawn position
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Oscillation {
+    pub amplitude: f32,
+    pub period: f32,
+    pub age: f32,
+}
+
+impl Oscillation {
+    fn offset(&self, age: f32) -> f32 {
+        self.amplitude * (TAU * age / self.period).sin()
+    }
+}
+
+/// Wall sliding by `travel` and back, once per period
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Gate {
+    /// Signed distance to the closed position
+    pub travel: f32,
+    pub period: f32,
+    pub age: f32,
+}
+
+impl Gate {
+    /// Bottom and top walls of a column, closing `travel` of the half of the
+    /// lowest and of the highest gap
+    pub fn outer_walls(travel: f32, period: f32, lowest_gap: f32, highest_gap: f32) -> [Gate; 2] {
+        let wall = |travel| Gate {
+            travel,
+            period,
+            age: 0.0,
+        };
+        [
+            wall(travel * lowest_gap / 2.0),
+            wall(-travel * highest_gap / 2.0),
+        ]
+    }
+
+    /// Open for the first half of the period, closed for the second half
+    pub fn offset(&self, age: f32) -> f32 {
+        let half = self.period / 2.0;
+        let transition = GATE_TRANSITION.min(half);
+        let phase = age % self.period;
+        let closing = if phase < half - transition {
+            0.0
+        } else if phase < half {
+            (phase - (half - transition)) / transition
+        } else if phase < self.period - transition {
+            1.0
+        } else {
+            1.0 - (phase - (self.period - transition)) / transition
+        };
+        self.travel * closing
+    }
+}
+
+/// Horizontal acceleration toward the player
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Charge {
+    pub acceleration: f32,
+}
+
+/// Velocity covering exactly the offset change during the tick
+fn tick_velocity(from: f32, to: f32, delta_t: f32) -> f32 {
+    if delta_t > 0.0 {
+        (to - from) / delta_t
+    } else {
+        0.0
+    }
+}
+
+pub fn oscillate(mut query: Query<(&mut Oscillation, &mut RigidBody)>, time: Res<Time>) {
+    let delta_t = time.delta_seconds();
+    for (mut oscillation, mut body) in query.iter_mut() {
+        let age = oscillation.age + delta_t;
+        body.velocity.y = tick_velocity(
+            oscillation.offset(oscillation.age),
+            oscillation.offset(age),
+            delta_t,
+        );
+        oscillation.age = age;
+    }
+}
+
+pub fn open_close_gates(mut query: Query<(&mut Gate, &mut RigidBody)>, time: Res<Time>) {
+    let delta_t = time.delta_seconds();
+    for (mut gate, mut body) in query.iter_mut() {
+        let age = gate.age + delta_t;
+        body.velocity.y = tick_velocity(gate.offset(gate.age), gate.offset(age), delta_t);
+        gate.age = age;
+    }
+}
+
+pub fn charge(
+    mut query: Query<(&Charge, &mut RigidBody), Without<Player>>,
+    player_query: Query<&RigidBody, With<Player>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (charge, mut body) in query.iter_mut() {
+        body.acceleration.x = if body.position.x > player.position.x {
+            -charge.acceleration
+        } else {
+            0.0
+        };
+    }
+}
@@ -0,0 +1,148 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Frosty mountains behind the fox, scrolling slower the farther they are
+///
+/// Each layer is a sprite tiled horizontally, one tile wider than the world.
+/// Scrolling moves it left by up to a tile, then wraps around.
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::daylight::Daylit;
+
+use super::Difficulty;
+
+/// World units per pixel of the layer textures
+const PIXEL_SIZE: f32 = 4.0;
+
+struct LayerStyle {
+    texture: &'static str,
+    /// Size of the texture in pixels
+    size: UVec2,
+    /// Fraction of the obstacle speed
+    speed_factor: f32,
+    z: f32,
+}
+
+/// From the farthest to the nearest
+const LAYERS: [LayerStyle; 4] = [
+    LayerStyle {
+        texture: "embedded://background-sky.png",
+        size: UVec2::new(64, 200),
+        speed_factor: 0.02,
+        z: -40.0,
+    },
+    LayerStyle {
+        texture: "embedded://background-peaks.png",
+        size: UVec2::new(160, 70),
+        speed_factor: 0.1,
+        z: -30.0,
+    },
+    LayerStyle {
+        texture: "embedded://background-hills.png",
+        size: UVec2::new(128, 40),
+        speed_factor: 0.25,
+        z: -20.0,
+    },
+    LayerStyle {
+        texture: "embedded://background-snow.png",
+        size: UVec2::new(64, 14),
+        speed_factor: 0.5,
+        z: -10.0,
+    },
+];
+
+#[derive(Component)]
+pub struct ParallaxLayer {
+    index: usize,
+    /// Distance scrolled since the last wrap around
+    offset: f32,
+}
+
+impl ParallaxLayer {
+    fn style(&self) -> &'static LayerStyle {
+        &LAYERS[self.index]
+    }
+
+    fn tile_width(&self) -> f32 {
+        self.style().size.x as f32 * PIXEL_SIZE
+    }
+
+    /// The sky fills the world height, the other layers stand on the ground
+    fn height(&self, config: &GameConfig) -> f32 {
+        if self.index == 0 {
+            config.world.height
+        } else {
+            self.style().size.y as f32 * PIXEL_SIZE
+        }
+    }
+
+    fn size(&self, config: &GameConfig) -> Vec2 {
+        Vec2::new(
+            config.world.width() + self.tile_width(),
+            self.height(config),
+        )
+    }
+
+    fn translation(&self, config: &GameConfig) -> Vec3 {
+        Vec3::new(
+            self.tile_width() / 2.0 - self.offset,
+            config.world.bottom() + self.height(config) / 2.0,
+            self.style().z,
+        )
+    }
+}
+
+pub fn spawn_layers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+) {
+    for (index, style) in LAYERS.iter().enumerate() {
+        let layer = ParallaxLayer { index, offset: 0.0 };
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(style.texture),
+                sprite: Sprite {
+                    custom_size: Some(layer.size(&config)),
+                    ..default()
+                },
+                transform: Transform::from_translation(layer.translation(&config)),
+                ..default()
+            },
+            ImageScaleMode::Tiled {
+                tile_x: true,
+                tile_y: false,
+                stretch_value: PIXEL_SIZE,
+            },
+            Daylit::default(),
+            layer,
+        ));
+    }
+}
+
+/// Follow the obstacles, so the layers stop with the game
+pub fn scroll_layers(
+    mut query: Query<(&mut ParallaxLayer, &mut Transform)>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    for (mut layer, mut transform) in query.iter_mut() {
+        let distance =
+            layer.style().speed_factor * difficulty.obstacle_speed * time.delta_seconds();
+        layer.offset = (layer.offset + distance) % layer.tile_width();
+        transform.translation = layer.translation(&config);
+    }
+}
+
+pub fn resize_layers(
+    config: Res<GameConfig>,
+    mut query: Query<(&ParallaxLayer, &mut Sprite, &mut Transform)>,
+) {
+    for (layer, mut sprite, mut transform) in query.iter_mut() {
+        sprite.custom_size = Some(layer.size(&config));
+        transform.translation = layer.translation(&config);
+    }
+}
@@ -0,0 +1,234 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Authored obstacle patterns, picked at random while the difficulty allows them
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::config::{not_below, positive};
+
+use super::motion::Motion;
+
+/// Group of obstacle columns spawned together
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ObstaclePattern {
+    pub name: String,
+    /// Relative chance of being picked among the available patterns
+    pub weight: f32,
+    /// Range of difficulty level, from 0 to 1, in which the pattern is available
+    #[serde(default)]
+    pub min_level: f32,
+    #[serde(default = "max_level")]
+    pub max_level: f32,
+    pub columns: Vec<Column>,
+}
+
+/// Walls from the bottom to the top of the world, except for the gaps
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Column {
+    /// Distance behind the first column of the pattern
+    #[serde(default)]
+    pub offset: f32,
+    pub gaps: Vec<Gap>,
+    #[serde(default)]
+    pub motion: Option<Motion>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    /// Height of the gap center above the anchor of the pattern
+    #[serde(default)]
+    pub position: f32,
+    /// Factor of the gap size of the current difficulty
+    #[serde(default = "unit_size")]
+    pub size: f32,
+}
+
+fn max_level() -> f32 {
+    1.0
+}
+
+fn unit_size() -> f32 {
+    1.0
+}
+
+impl ObstaclePattern {
+    /// Distance between the first and the last column
+    pub fn length(&self) -> f32 {
+        self.columns
+            .iter()
+            .map(|column| column.offset)
+            .fold(0.0, f32::max)
+    }
+
+    /// Lowest and highest gap position, relative to the anchor
+    pub fn gap_range(&self) -> (f32, f32) {
+        self.columns
+            .iter()
+            .flat_map(|column| column.gaps.iter())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), gap| {
+                (low.min(gap.position), high.max(gap.position))
+            })
+    }
+
+    /// Highest vertical distance the gaps move away from their spawn position
+    pub fn gap_travel(&self) -> f32 {
+        self.columns
+            .iter()
+            .filter_map(|column| column.motion.as_ref())
+            .map(Motion::gap_travel)
+            .fold(0.0, f32::max)
+    }
+
+    /// Every column needs a gap, for the spawner to place the pattern, and a
+    /// motion it can follow, the pattern a chance of being picked
+    pub fn validate(&self) -> Result<(), String> {
+        positive(&format!("Pattern {} weight", self.name), self.weight)?;
+        not_below(
+            &format!("Pattern {} max_level", self.name),
+            self.max_level,
+            self.min_level,
+        )?;
+        if self.columns.is_empty() {
+            return Err(format!("Pattern {} has no column", self.name));
+        }
+        if self.columns.iter().any(|column| column.gaps.is_empty()) {
+            return Err(format!("Pattern {} has a column without gap", self.name));
+        }
+        for motion in self.columns.iter().filter_map(|column| column.motion) {
+            motion
+                .validate()
+                .map_err(|err| format!("Pattern {}: {}", self.name, err))?;
+        }
+        Ok(())
+    }
+
+    /// Same pattern upside down
+    pub fn mirrored(&self) -> Self {
+        let mut pattern = self.clone();
+        for gap in pattern
+            .columns
+            .iter_mut()
+            .flat_map(|column| column.gaps.iter_mut())
+        {
+            gap.position = -gap.position;
+        }
+        pattern
+    }
+}
+
+/// Weighted pick among the patterns available at the difficulty `level`
+pub fn choose_pattern<'a, R: Rng>(
+    patterns: &'a [ObstaclePattern],
+    level: f32,
+    rng: &mut R,
+) -> Option<&'a ObstaclePattern> {
+    let available: Vec<&ObstaclePattern> = patterns
+        .iter()
+        .filter(|pattern| (pattern.min_level..=pattern.max_level).contains(&level))
+        .collect();
+    available
+        .choose_weighted(rng, |pattern| pattern.weight)
+        .ok()
+        .copied()
+}
+
+fn column(offset: f32, gaps: &[(f32, f32)]) -> Column {
+    Column {
+        offset,
+        gaps: gaps
+            .iter()
+            .map(|&(position, size)| Gap { position, size })
+            .collect(),
+        motion: None,
+    }
+}
+
+fn pattern(name: &str, weight: f32, min_level: f32, columns: Vec<Column>) -> ObstaclePattern {
+    ObstaclePattern {
+        name: name.to_string(),
+        weight,
+        min_level,
+        max_level: 1.0,
+        columns,
+    }
+}
+
+/// Patterns used when the configuration does not list any
+pub fn default_patterns() -> Vec<ObstaclePattern> {
+    vec![
+        pattern("single", 4.0, 0.0, vec![column(0.0, &[(0.0, 1.0)])]),
+        pattern(
+            "staircase",
+            2.0,
+            0.1,
+            vec![
+                column(0.0, &[(-80.0, 1.0)]),
+                column(80.0, &[(0.0, 1.0)]),
+                column(160.0, &[(80.0, 1.0)]),
+            ],
+        ),
+        pattern(
+            "zigzag",
+            2.0,
+            0.2,
+            vec![
+                column(0.0, &[(-70.0, 1.0)]),
+                column(100.0, &[(70.0, 1.0)]),
+                column(200.0, &[(-70.0, 1.0)]),
+                column(300.0, &[(70.0, 1.0)]),
+            ],
+        ),
+        pattern(
+            "double-gap",
+            1.5,
+            0.3,
+            vec![column(0.0, &[(-140.0, 0.8), (140.0, 0.8)])],
+        ),
+        pattern(
+            "moving-gate",
+            1.5,
+            0.4,
+            vec![Column {
+                motion: Some(Motion::Oscillate {
+                    amplitude: 60.0,
+                    period: 3.0,
+                }),
+                ..column(0.0, &[(0.0, 1.1)])
+            }],
+        ),
+        pattern(
+            "closing-gate",
+            1.0,
+            0.45,
+            vec![Column {
+                motion: Some(Motion::Gate {
+                    travel: 1.0,
+                    period: 2.5,
+                }),
+                ..column(0.0, &[(0.0, 1.2)])
+            }],
+        ),
+        pattern(
+            "narrow-tunnel",
+            1.0,
+            0.5,
+            vec![
+                column(0.0, &[(0.0, 0.8)]),
+                column(40.0, &[(0.0, 0.8)]),
+                column(80.0, &[(0.0, 0.8)]),
+            ],
+        ),
+        pattern(
+            "charging-wall",
+            1.0,
+            0.6,
+            vec![Column {
+                motion: Some(Motion::Charge { acceleration: 60.0 }),
+                ..column(0.0, &[(0.0, 1.0)])
+            }],
+        ),
+    ]
+}
@@ -0,0 +1,74 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Reuse the wall and waypoint entities instead of spawning new ones
+///
+/// A released entity keeps its sprite, hidden, and loses its gameplay
+/// components, so the obstacle systems no longer see it.
+use bevy::prelude::*;
+
+use crate::daylight::Daylit;
+use crate::physics::{Collider, RigidBody};
+
+use super::motion::{Charge, Gate, Oscillation};
+use super::terrain::{Ground, Platform};
+use super::{Obstacle, Waypoint};
+
+/// Enough for the longest patterns on screen at the hardest difficulty
+const POOL_SIZE: usize = 48;
+
+/// Entity owned by the pool, in use or not
+#[derive(Component)]
+pub struct Pooled;
+
+#[derive(Resource, Default)]
+pub struct ObstaclePool {
+    free: Vec<Entity>,
+}
+
+impl ObstaclePool {
+    /// Entity of a free obstacle, a new one when they are all in use
+    pub fn acquire(&mut self, commands: &mut Commands) -> Entity {
+        self.free
+            .pop()
+            .unwrap_or_else(|| commands.spawn(pooled_bundle()).id())
+    }
+
+    /// Hide the obstacle and keep it for a later use
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        commands
+            .entity(entity)
+            .remove::<(
+                Obstacle,
+                Waypoint,
+                Ground,
+                Platform,
+                Collider,
+                RigidBody,
+                Oscillation,
+                Gate,
+                Charge,
+            )>()
+            .insert(Visibility::Hidden);
+        self.free.push(entity);
+    }
+}
+
+fn pooled_bundle() -> impl Bundle {
+    (
+        SpriteBundle {
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Daylit::default(),
+        Pooled,
+    )
+}
+
+pub fn setup_pool(mut commands: Commands, mut pool: ResMut<ObstaclePool>) {
+    for _ in 0..POOL_SIZE {
+        let entity = commands.spawn(pooled_bundle()).id();
+        pool.free.push(entity);
+    }
+}
@@ -0,0 +1,83 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{command_line_arg, RestartEvent};
+
+use super::ObstacleSpawnTimer;
+
+/// Seed of the world generation.
+/// Two runs with the same seed and the same inputs produce the same course.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldSeed {
+    pub value: u64,
+    /// Keep the same seed for every run instead of drawing a new one on restart
+    pub locked: bool,
+}
+
+impl WorldSeed {
+    pub fn locked(value: u64) -> Self {
+        WorldSeed {
+            value,
+            locked: true,
+        }
+    }
+}
+
+impl Default for WorldSeed {
+    /// Use the seed given on the command line with `--seed <n>`, or a random one
+    fn default() -> Self {
+        if let Some(seed) = command_line_arg("--seed").and_then(|s| s.parse().ok()) {
+            return WorldSeed::locked(seed);
+        }
+        WorldSeed {
+            value: rand::random(),
+            locked: false,
+        }
+    }
+}
+
+/// Random number generator every random decision of the world draws from
+///
+/// ChaCha8 is portable, a seed gives the same course on every platform and
+/// with every version of rand, unlike `StdRng`.
+#[derive(Resource, Deref, DerefMut)]
+pub struct WorldRng(pub ChaCha8Rng);
+
+impl WorldRng {
+    pub fn from_seed(seed: &WorldSeed) -> Self {
+        WorldRng(ChaCha8Rng::seed_from_u64(seed.value))
+    }
+}
+
+pub fn setup_rng(mut commands: Commands, seed: Res<WorldSeed>) {
+    info!("World seed {}", seed.value);
+    commands.insert_resource(WorldRng::from_seed(&seed));
+}
+
+/// Start the new run from the beginning of its seeded sequence
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    mut seed: ResMut<WorldSeed>,
+    mut rng: ResMut<WorldRng>,
+    mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+) {
+    if !events.is_empty() {
+        // A retry wins over a plain restart sent along
+        let mut same_seed = false;
+        for event in events.read() {
+            same_seed |= event.same_seed;
+        }
+        if !seed.locked && !same_seed {
+            seed.value = rand::random();
+        }
+        info!("World seed {}", seed.value);
+        *rng = WorldRng::from_seed(&seed);
+        spawn_timer.timer.reset();
+        spawn_timer.pattern_delay = 0.0;
+    }
+}
@@ -0,0 +1,189 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Ground of the running mode, scrolling like the walls
+///
+/// The ground is made of pieces with pits in between, spawned ahead of the
+/// fox. Some pieces have spikes, some pits have a one-way platform floating in
+/// their middle. Falling in a pit ends on the bottom border of the world.
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::config::{GameConfig, GameMode};
+use crate::physics::{Collider, CollisionLayers, RigidBody};
+
+use super::{
+    spawn_waypoint, Difficulty, Obstacle, ObstaclePool, ObstacleTextures, ObstacleTheme,
+    ScoreBoard, WorldRng,
+};
+
+const SPIKE_SIZE: Vec2 = Vec2::new(24.0, 24.0);
+const PLATFORM_THICKNESS: f32 = 20.0;
+/// Shortest piece of ground, the ground always extends and a spike fits on it
+pub const MIN_GROUND_LENGTH: f32 = 3.0 * SPIKE_SIZE.x;
+
+/// Piece of ground or floating platform, the fox lands on its top
+#[derive(Component)]
+pub struct Ground;
+
+/// Floating platform, not extending the ground, the fox jumps through it
+/// from below
+#[derive(Component)]
+pub struct Platform;
+
+pub fn running_mode(config: Res<GameConfig>) -> bool {
+    config.mode == GameMode::Running
+}
+
+/// Spawn the ground ahead of the fox, a whole screen at the start of a run
+#[allow(clippy::too_many_arguments)]
+pub fn extend_ground(
+    mut commands: Commands,
+    ground: Query<(&RigidBody, &Collider, Has<Platform>), With<Ground>>,
+    mut pool: ResMut<ObstaclePool>,
+    textures: Res<ObstacleTextures>,
+    mut rng: ResMut<WorldRng>,
+    scorebard: Res<ScoreBoard>,
+    difficulty: Res<Difficulty>,
+    config: Res<GameConfig>,
+) {
+    let terrain = &config.terrain;
+    let level = config.difficulty.level(scorebard.score, scorebard.run_time);
+    let velocity = Vec2::new(-difficulty.obstacle_speed, 0.0);
+    let look = |theme| (&*textures, theme);
+
+    let mut end = ground
+        .iter()
+        .filter(|(.., platform)| !platform)
+        .map(|(body, collider, _)| body.position.x + collider.shape.half_extents().x)
+        .reduce(f32::max);
+    while end.is_none_or(|end| end < config.world.right() + terrain.max_ground_length) {
+        let (start, length) = match end {
+            // Flat ground under the fox to start the run
+            None => (config.world.left(), config.world.width() * 0.75),
+            Some(end) => {
+                let max_width = terrain.min_pit_width.lerp(terrain.max_pit_width, level);
+                let mut width = rng.gen_range(terrain.min_pit_width..=max_width);
+                if rng.gen_bool(terrain.platform_chance as f64) {
+                    width = 2.0 * width + terrain.platform_width;
+                    let position = Vec2::new(
+                        end + width / 2.0,
+                        ground_top(&config) + terrain.platform_height - PLATFORM_THICKNESS / 2.0,
+                    );
+                    let size = Vec2::new(terrain.platform_width, PLATFORM_THICKNESS);
+                    let platform = spawn_ground(
+                        &mut commands,
+                        &mut pool,
+                        look(ObstacleTheme::PineTrunk),
+                        position,
+                        size,
+                        velocity,
+                        true,
+                    );
+                    commands.entity(platform).insert(Platform);
+                }
+                // Scores when jumping over the pit
+                let bottom = ground_top(&config);
+                let height = config.world.top() - bottom;
+                let position = Vec2::new(end + width / 2.0, bottom + height / 2.0);
+                spawn_waypoint(
+                    &mut commands,
+                    &mut pool,
+                    position,
+                    height,
+                    velocity,
+                    &config,
+                );
+
+                let length = rng.gen_range(terrain.min_ground_length..=terrain.max_ground_length);
+                (end + width, length)
+            }
+        };
+
+        let height = terrain.ground_height;
+        let position = Vec2::new(start + length / 2.0, config.world.bottom() + height / 2.0);
+        spawn_ground(
+            &mut commands,
+            &mut pool,
+            look(ObstacleTheme::RockCliff),
+            position,
+            Vec2::new(length, height),
+            velocity,
+            false,
+        );
+        if end.is_some() && rng.gen_bool(terrain.spike_chance as f64) {
+            // Away from the pits, to land before jumping again
+            let x = rng.gen_range(start + length * 0.3..=start + length * 0.7);
+            let position = Vec2::new(x, ground_top(&config) + SPIKE_SIZE.y / 2.0);
+            spawn_spike(
+                &mut commands,
+                &mut pool,
+                look(ObstacleTheme::IcyPillar),
+                position,
+                velocity,
+            );
+        }
+        end = Some(start + length);
+    }
+}
+
+fn ground_top(config: &GameConfig) -> f32 {
+    config.world.bottom() + config.terrain.ground_height
+}
+
+/// Piece of ground, or platform when `one_way`
+fn spawn_ground(
+    commands: &mut Commands,
+    pool: &mut ObstaclePool,
+    (textures, theme): (&ObstacleTextures, ObstacleTheme),
+    position: Vec2,
+    size: Vec2,
+    velocity: Vec2,
+    one_way: bool,
+) -> Entity {
+    let collider =
+        Collider::aabb(size / 2.0).with_layers(CollisionLayers::GROUND, CollisionLayers::NONE);
+    let ground = pool.acquire(commands);
+    commands.entity(ground).insert((
+        Transform::from_translation(position.extend(0.0)),
+        textures.wall(theme, size),
+        Visibility::Inherited,
+        Obstacle,
+        Ground,
+        if one_way {
+            collider.one_way()
+        } else {
+            collider
+        },
+        RigidBody {
+            position,
+            velocity,
+            ..Default::default()
+        },
+    ));
+    ground
+}
+
+fn spawn_spike(
+    commands: &mut Commands,
+    pool: &mut ObstaclePool,
+    (textures, theme): (&ObstacleTextures, ObstacleTheme),
+    position: Vec2,
+    velocity: Vec2,
+) {
+    let spike = pool.acquire(commands);
+    commands.entity(spike).insert((
+        Transform::from_translation(position.extend(0.0)),
+        textures.wall(theme, SPIKE_SIZE),
+        Visibility::Inherited,
+        Obstacle,
+        Collider::aabb(SPIKE_SIZE / 2.0)
+            .with_layers(CollisionLayers::OBSTACLE, CollisionLayers::NONE),
+        RigidBody {
+            position,
+            velocity,
+            ..Default::default()
+        },
+    ));
+}
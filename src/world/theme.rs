@@ -0,0 +1,93 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Look of the walls, drawn from the tiles of `obstacles.png`
+///
+/// The walls are nine-sliced: the caps at both ends keep their size and the
+/// body is tiled, so a wall of any height looks the same. The sprite has its
+/// own width, the collider keeps the obstacle width.
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const TILE_WIDTH: u32 = 32;
+const TILE_HEIGHT: u32 = 64;
+/// Height of the caps, in pixels of the texture
+const CAP_HEIGHT: f32 = 8.0;
+
+/// Tile of the atlas, every wall of a pattern uses the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObstacleTheme {
+    IcyPillar,
+    RockCliff,
+    PineTrunk,
+}
+
+impl ObstacleTheme {
+    const ALL: [ObstacleTheme; 3] = [
+        ObstacleTheme::IcyPillar,
+        ObstacleTheme::RockCliff,
+        ObstacleTheme::PineTrunk,
+    ];
+
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        *Self::ALL.choose(rng).unwrap()
+    }
+
+    fn atlas_index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Resource)]
+pub struct ObstacleTextures {
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+impl ObstacleTextures {
+    /// Sprite of a wall drawn at `size`
+    pub fn wall(
+        &self,
+        theme: ObstacleTheme,
+        size: Vec2,
+    ) -> (Sprite, Handle<Image>, TextureAtlas, ImageScaleMode) {
+        (
+            Sprite {
+                custom_size: Some(size),
+                ..default()
+            },
+            self.texture.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index: theme.atlas_index(),
+            },
+            ImageScaleMode::Sliced(TextureSlicer {
+                border: BorderRect::rectangle(0.0, CAP_HEIGHT),
+                center_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                sides_scale_mode: SliceScaleMode::Tile { stretch_value: 1.0 },
+                max_corner_scale: 1.0,
+            }),
+        )
+    }
+}
+
+pub fn setup_textures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let texture = asset_server.load("embedded://obstacles.png");
+    let layout = TextureAtlasLayout::from_grid(
+        UVec2::new(TILE_WIDTH, TILE_HEIGHT),
+        ObstacleTheme::ALL.len() as u32,
+        1,
+        None,
+        None,
+    );
+    commands.insert_resource(ObstacleTextures {
+        texture,
+        layout: texture_atlas_layouts.add(layout),
+    });
+}
@@ -0,0 +1,283 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+/// Weather of the run, from a clear sky to a blizzard
+///
+/// At the end of each spell the weather gets one step better or worse,
+/// worsening more often as the difficulty rises. The snowfall particles and
+/// the fog around the edges of the screen follow the current weather.
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_particle_systems::*;
+use rand::Rng;
+
+use crate::config::{GameConfig, WeatherConfig};
+use crate::RestartEvent;
+
+use super::{ScoreBoard, Wind, WorldRng};
+
+/// Speed of the falling snow, without wind
+const SNOW_FALL_SPEED: f32 = 80.0;
+/// Share of the wind acceleration blowing the snow
+const SNOW_DRIFT: f32 = 0.5;
+/// Fog opacity change per second
+const FOG_RATE: f32 = 0.2;
+/// Size in pixels of the fog texture
+const FOG_TEXTURE_SIZE: u32 = 64;
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    LightSnow,
+    HeavySnow,
+    Blizzard,
+}
+
+impl Weather {
+    fn worse(self) -> Self {
+        match self {
+            Weather::Clear => Weather::LightSnow,
+            Weather::LightSnow => Weather::HeavySnow,
+            _ => Weather::Blizzard,
+        }
+    }
+
+    fn better(self) -> Self {
+        match self {
+            Weather::Blizzard => Weather::HeavySnow,
+            Weather::HeavySnow => Weather::LightSnow,
+            _ => Weather::Clear,
+        }
+    }
+
+    /// Snowflakes per second
+    fn snow_rate(self) -> f32 {
+        match self {
+            Weather::Clear => 0.0,
+            Weather::LightSnow => 15.0,
+            Weather::HeavySnow => 60.0,
+            Weather::Blizzard => 160.0,
+        }
+    }
+
+    fn snowflake_scale(self) -> f32 {
+        match self {
+            Weather::Clear | Weather::LightSnow => 2.0,
+            Weather::HeavySnow => 3.0,
+            Weather::Blizzard => 4.0,
+        }
+    }
+
+    /// Opacity of the fog at the edges of the screen
+    fn fog(self) -> f32 {
+        match self {
+            Weather::Clear => 0.0,
+            Weather::LightSnow => 0.2,
+            Weather::HeavySnow => 0.45,
+            Weather::Blizzard => 0.8,
+        }
+    }
+}
+
+/// Time left before the weather changes
+#[derive(Resource)]
+pub struct WeatherSpell {
+    timer: Timer,
+}
+
+impl WeatherSpell {
+    /// Shortest spell, for the clear sky of the start
+    pub fn new(config: &WeatherConfig) -> Self {
+        WeatherSpell {
+            timer: Timer::from_seconds(config.min_spell, TimerMode::Once),
+        }
+    }
+}
+
+impl FromWorld for WeatherSpell {
+    fn from_world(world: &mut World) -> Self {
+        WeatherSpell::new(&world.resource::<GameConfig>().weather)
+    }
+}
+
+#[derive(Component)]
+pub struct Snowfall;
+
+#[derive(Component)]
+pub struct Fog {
+    opacity: f32,
+}
+
+pub fn update_weather(
+    mut weather: ResMut<Weather>,
+    mut spell: ResMut<WeatherSpell>,
+    mut rng: ResMut<WorldRng>,
+    scorebard: Res<ScoreBoard>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    spell.timer.tick(time.delta());
+    if !spell.timer.finished() {
+        return;
+    }
+
+    let weather_config = &config.weather;
+    let level = config.difficulty.level(scorebard.score, scorebard.run_time);
+    let worsen_chance = weather_config.easiest_worsen_chance
+        + (weather_config.hardest_worsen_chance - weather_config.easiest_worsen_chance) * level;
+    let next = if rng.gen_bool(worsen_chance.clamp(0.0, 1.0) as f64) {
+        weather.worse()
+    } else {
+        weather.better()
+    };
+    weather.set_if_neq(next);
+    info!("Weather {:?}", next);
+
+    let min_spell = weather_config.min_spell;
+    let duration = rng.gen_range(min_spell..=weather_config.max_spell.max(min_spell));
+    spell.timer.set_duration(Duration::from_secs_f32(duration));
+    spell.timer.reset();
+}
+
+/// Time the first spell with the `min_spell` of a reloaded configuration
+pub fn reset_spell(config: Res<GameConfig>, mut spell: ResMut<WeatherSpell>) {
+    *spell = WeatherSpell::new(&config.weather);
+}
+
+/// Start the new run with a clear sky
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    config: Res<GameConfig>,
+    mut weather: ResMut<Weather>,
+    mut spell: ResMut<WeatherSpell>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        weather.set_if_neq(Weather::Clear);
+        *spell = WeatherSpell::new(&config.weather);
+    }
+}
+
+pub fn spawn_weather(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    config: Res<GameConfig>,
+) {
+    commands.spawn((
+        ParticleSystemBundle {
+            particle_system: ParticleSystem {
+                max_particles: 2048,
+                emitter_shape: EmitterShape::Line(Line {
+                    length: config.world.width(),
+                    angle: JitteredValue::jittered(-std::f32::consts::FRAC_PI_2, -0.2..0.2),
+                }),
+                spawn_rate_per_second: 0.0.into(),
+                initial_speed: JitteredValue::jittered(SNOW_FALL_SPEED, -20.0..20.0),
+                lifetime: JitteredValue::jittered(12.0, -2.0..2.0),
+                color: ColorOverTime::Constant(Color::WHITE),
+                initial_scale: JitteredValue::jittered(2.0, -0.5..0.5),
+                looping: true,
+                system_duration_seconds: 10.0,
+                ..ParticleSystem::default()
+            },
+            transform: Transform::from_xyz(0.0, config.world.top(), 0.0),
+            ..ParticleSystemBundle::default()
+        },
+        Playing,
+        Snowfall,
+    ));
+
+    commands.spawn((
+        SpriteBundle {
+            texture: images.add(fog_image()),
+            sprite: Sprite {
+                color: Color::srgba(0.55, 0.6, 0.7, 0.0),
+                custom_size: Some(Vec2::new(config.world.width(), config.world.height)),
+                ..default()
+            },
+            // Above the world, below the pause overlay
+            transform: Transform::from_xyz(0.0, 0.0, 0.5),
+            ..default()
+        },
+        Fog { opacity: 0.0 },
+    ));
+}
+
+/// Transparent in the middle, opaque at the edges
+fn fog_image() -> Image {
+    let size = FOG_TEXTURE_SIZE;
+    let center = (size as f32 - 1.0) / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = Vec2::new(x as f32 - center, y as f32 - center).length() / center;
+            let alpha = ((distance - 0.4) / 0.6).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Snow falls with the weather and drifts with the upcoming wind
+pub fn drive_snowfall(
+    weather: Res<Weather>,
+    wind: Res<Wind>,
+    mut query: Query<&mut ParticleSystem, With<Snowfall>>,
+) {
+    let velocity = Vec2::new(0.0, -SNOW_FALL_SPEED) + wind.upcoming * SNOW_DRIFT;
+    for mut particle_system in query.iter_mut() {
+        particle_system.spawn_rate_per_second = weather.snow_rate().into();
+        particle_system.initial_scale =
+            JitteredValue::jittered(weather.snowflake_scale(), -0.5..0.5);
+        particle_system.initial_speed = JitteredValue::jittered(velocity.length(), -20.0..20.0);
+        if let EmitterShape::Line(line) = &mut particle_system.emitter_shape {
+            line.angle = JitteredValue::jittered(velocity.to_angle(), -0.2..0.2);
+        }
+    }
+}
+
+/// Thicken or clear the fog toward the current weather
+pub fn update_fog(
+    weather: Res<Weather>,
+    mut query: Query<(&mut Fog, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    let target = weather.fog();
+    for (mut fog, mut sprite) in query.iter_mut() {
+        let step = FOG_RATE * time.delta_seconds();
+        fog.opacity += (target - fog.opacity).clamp(-step, step);
+        sprite.color.set_alpha(fog.opacity);
+    }
+}
+
+/// Cover the whole world when the configuration is reloaded
+pub fn resize_weather(
+    config: Res<GameConfig>,
+    mut snowfall: Query<(&mut ParticleSystem, &mut Transform), With<Snowfall>>,
+    mut fog: Query<&mut Sprite, With<Fog>>,
+) {
+    for (mut particle_system, mut transform) in snowfall.iter_mut() {
+        if let EmitterShape::Line(line) = &mut particle_system.emitter_shape {
+            line.length = config.world.width();
+        }
+        transform.translation.y = config.world.top();
+    }
+    for mut sprite in fog.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(config.world.width(), config.world.height));
+    }
+}
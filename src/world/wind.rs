@@ -6,19 +6,140 @@
  * Copyright (c) 2024 Louis Mayencourt
  */
 
+use std::f32::consts::PI;
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_particle_systems::*;
+use rand::Rng;
+
+use crate::config::{GameConfig, WindConfig};
+use crate::RestartEvent;
+
+use super::WorldRng;
+
+/// Speed and spawn rate of the particles, growing with the wind strength
+const PARTICLE_SPEED: f32 = 120.0;
+const PARTICLE_SPEED_FACTOR: f32 = 0.4;
+const PARTICLE_RATE: f32 = 40.0;
+const PARTICLE_RATE_FACTOR: f32 = 0.1;
+
+/// Streaks blown from the right edge
+#[derive(Component)]
+pub struct WindStreaks;
+
+/// Wind blowing over the world, pushing the fox
+///
+/// The wind changes linearly to `upcoming` over the change time. The particles
+/// already blow like the upcoming wind, so the gusts can be seen coming.
+#[derive(Resource, Debug, Clone)]
+pub struct Wind {
+    /// Acceleration applied to the fox
+    pub acceleration: Vec2,
+    /// Acceleration at the end of the change
+    pub upcoming: Vec2,
+    change: Timer,
+}
+
+impl Wind {
+    /// Light breeze, until the first change
+    pub fn new(config: &WindConfig) -> Self {
+        let breeze = Vec2::new(-config.lull_strength, 0.0);
+        Wind {
+            acceleration: breeze,
+            upcoming: breeze,
+            change: Timer::from_seconds(config.min_change_time, TimerMode::Once),
+        }
+    }
+}
+
+impl FromWorld for Wind {
+    fn from_world(world: &mut World) -> Self {
+        Wind::new(&world.resource::<GameConfig>().wind)
+    }
+}
 
-use super::{WORLD_BOTTOM, WORLD_HEIGHT, WORLD_RIGHT, WORLD_WIDTH};
+pub fn update_wind(
+    mut wind: ResMut<Wind>,
+    mut rng: ResMut<WorldRng>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let remaining = wind.change.remaining_secs();
+    let step = if remaining > 0.0 {
+        (time.delta_seconds() / remaining).min(1.0)
+    } else {
+        1.0
+    };
+    wind.acceleration = wind.acceleration.lerp(wind.upcoming, step);
+    wind.change.tick(time.delta());
 
-pub fn spawn_particle_system(mut commands: Commands) {
+    if wind.change.finished() {
+        let wind_config = &config.wind;
+        let lull = wind_config.lull_strength;
+        let strength = if rng.gen_bool(wind_config.gust_chance.clamp(0.0, 1.0) as f64) {
+            rng.gen_range(lull..=wind_config.gust_strength.max(lull))
+        } else {
+            rng.gen_range(0.0..=lull)
+        };
+        let angle = PI + rng.gen_range(-wind_config.max_angle..=wind_config.max_angle);
+        wind.upcoming = Vec2::from_angle(angle) * strength;
+
+        let min_time = wind_config.min_change_time;
+        let change_time = rng.gen_range(min_time..=wind_config.max_change_time.max(min_time));
+        wind.change
+            .set_duration(Duration::from_secs_f32(change_time));
+        wind.change.reset();
+    }
+}
+
+/// Start from the breeze of a reloaded configuration, the landing screen
+/// has no wind change yet
+pub fn reset_wind(config: Res<GameConfig>, mut wind: ResMut<Wind>) {
+    *wind = Wind::new(&config.wind);
+}
+
+/// Start the new run with a light breeze
+pub fn restart_event_handler(
+    mut events: EventReader<RestartEvent>,
+    config: Res<GameConfig>,
+    mut wind: ResMut<Wind>,
+) {
+    if !events.is_empty() {
+        events.clear();
+        *wind = Wind::new(&config.wind);
+    }
+}
+
+/// Blow the particles like the upcoming wind
+pub fn drive_particles(wind: Res<Wind>, mut query: Query<&mut ParticleSystem, With<WindStreaks>>) {
+    let strength = wind.upcoming.length();
+    let angle = if strength > 0.0 {
+        wind.upcoming.to_angle()
+    } else {
+        PI
+    };
+    for mut particle_system in query.iter_mut() {
+        particle_system.initial_speed = JitteredValue::jittered(
+            PARTICLE_SPEED + PARTICLE_SPEED_FACTOR * strength,
+            -30.0..30.0,
+        );
+        particle_system.spawn_rate_per_second =
+            (PARTICLE_RATE + PARTICLE_RATE_FACTOR * strength).into();
+        if let EmitterShape::Line(line) = &mut particle_system.emitter_shape {
+            line.angle = JitteredValue::jittered(angle, -0.1..0.1);
+        }
+    }
+}
+
+pub fn spawn_particle_system(mut commands: Commands, config: Res<GameConfig>) {
     commands
         // Add the bundle specifying the particle system itself.
         .spawn(ParticleSystemBundle {
             particle_system: ParticleSystem {
                 max_particles: 1024,
                 emitter_shape: EmitterShape::Line(Line {
-                    length: WORLD_WIDTH,
+                    length: config.world.width(),
                     angle: JitteredValue::jittered(std::f32::consts::PI, -0.1..0.1),
                 }),
                 spawn_rate_per_second: 50.0.into(),
@@ -33,9 +154,22 @@ pub fn spawn_particle_system(mut commands: Commands) {
                 system_duration_seconds: 10.0,
                 ..ParticleSystem::default()
             },
-            transform: Transform::from_xyz(WORLD_RIGHT, WORLD_BOTTOM + WORLD_HEIGHT / 2.0, 0.0),
+            transform: Transform::from_xyz(config.world.right(), 0.0, 0.0),
             ..ParticleSystemBundle::default()
         })
         // Add the playing component so it starts playing. This can be added later as well.
-        .insert(Playing);
+        .insert((Playing, WindStreaks));
+}
+
+/// Blow from the right edge over the whole width of the world
+pub fn resize_wind(
+    config: Res<GameConfig>,
+    mut query: Query<(&mut ParticleSystem, &mut Transform), With<WindStreaks>>,
+) {
+    for (mut particle_system, mut transform) in query.iter_mut() {
+        if let EmitterShape::Line(line) = &mut particle_system.emitter_shape {
+            line.length = config.world.width();
+        }
+        transform.translation.x = config.world.right();
+    }
 }
@@ -0,0 +1,159 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::asset::ron;
+
+use flynn::config::GameConfig;
+
+#[test]
+fn shipped_config_matches_the_defaults() {
+    let text = std::fs::read_to_string("assets/game.config.ron").unwrap();
+    let config: GameConfig = ron::from_str(&text).unwrap();
+    assert_eq!(config, GameConfig::default());
+}
+
+#[test]
+fn missing_fields_keep_their_default() {
+    let config: GameConfig = ron::from_str("(player: (gravity: -1000.0))").unwrap();
+    assert_eq!(config.player.gravity, -1000.0);
+    assert_eq!(config.player.jump_velocity, 450.0);
+    assert_eq!(config.world, GameConfig::default().world);
+}
+
+#[test]
+fn patterns_without_gaps_are_rejected() {
+    assert_eq!(GameConfig::default().validate(), Ok(()));
+
+    let config: GameConfig =
+        ron::from_str("(patterns: [(name: \"empty\", weight: 1.0, columns: [])])").unwrap();
+    assert!(config.validate().is_err());
+
+    let config: GameConfig =
+        ron::from_str("(patterns: [(name: \"wall\", weight: 1.0, columns: [(gaps: [])])])")
+            .unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn patterns_that_can_never_be_picked_are_rejected() {
+    let config: GameConfig =
+        ron::from_str("(patterns: [(name: \"unweighted\", weight: 0.0, columns: [(gaps: [()])])])")
+            .unwrap();
+    assert!(config.validate().is_err());
+
+    let config: GameConfig = ron::from_str(
+        "(patterns: [(name: \"inverted\", weight: 1.0, min_level: 0.8, max_level: 0.2,
+            columns: [(gaps: [()])])])",
+    )
+    .unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn motions_without_a_period_are_rejected() {
+    let config: GameConfig = ron::from_str(
+        "(patterns: [(name: \"frozen\", weight: 1.0, columns: [
+            (gaps: [()], motion: Some(Gate(travel: 1.0, period: 0.0))),
+        ])])",
+    )
+    .unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn gates_closing_past_their_gap_are_rejected() {
+    let config: GameConfig = ron::from_str(
+        "(patterns: [(name: \"crushing\", weight: 1.0, columns: [
+            (gaps: [()], motion: Some(Gate(travel: 1.5, period: 2.5))),
+        ])])",
+    )
+    .unwrap();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn durations_speeds_and_intervals_out_of_range_are_rejected() {
+    for text in [
+        "(world: (height: 0.0))",
+        "(world: (height: -800.0))",
+        "(obstacle: (width: 0.0))",
+        "(obstacle: (sprite_width: -28.0))",
+        "(obstacle: (despawn_interval: -0.2))",
+        "(obstacle: (despawn_interval: 0.0))",
+        "(difficulty: (easiest: (obstacle_speed: 0.0, spawn_interval: 2.0, gap_size: 200.0)))",
+        "(difficulty: (hardest: (obstacle_speed: -240.0, spawn_interval: 1.2, gap_size: 140.0)))",
+        "(difficulty: (easiest: (obstacle_speed: 120.0, spawn_interval: -2.0, gap_size: 200.0)))",
+        "(difficulty: (hardest: (obstacle_speed: 240.0, spawn_interval: 0.0, gap_size: 140.0)))",
+        "(difficulty: (hardest: (obstacle_speed: 240.0, spawn_interval: 1.2, gap_size: -140.0)))",
+        "(difficulty: (max_run_time: 0.0))",
+        "(difficulty: (max_score: 0))",
+        "(difficulty: (exponent: -1.5))",
+        "(difficulty: (exponent: 0.0))",
+        "(player: (jump_velocity: -450.0))",
+        "(player: (running_jump_velocity: 0.0))",
+        "(player: (jump_buffer: -0.1))",
+        "(player: (coyote_time: -0.1))",
+        "(player: (wall_slide_speed: -150.0))",
+        "(player: (wall_jump_push: -400.0))",
+        "(player: (animation_frame_time: -0.1))",
+        "(player: (animation_frame_time: 0.0))",
+        "(daylight: (day_length: 0.0))",
+    ] {
+        let config: GameConfig = ron::from_str(text).unwrap();
+        assert!(config.validate().is_err(), "{} is accepted", text);
+    }
+}
+
+#[test]
+fn zero_buffer_and_coyote_time_are_accepted() {
+    let config: GameConfig =
+        ron::from_str("(player: (jump_buffer: 0.0, coyote_time: 0.0))").unwrap();
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn wind_out_of_order_or_negative_is_rejected() {
+    for text in [
+        "(wind: (lull_strength: -60.0))",
+        "(wind: (lull_strength: 400.0, gust_strength: 360.0))",
+        "(wind: (gust_chance: 1.5))",
+        "(wind: (min_change_time: -2.0))",
+        "(wind: (min_change_time: 6.0, max_change_time: 5.0))",
+        "(wind: (max_angle: -0.35))",
+    ] {
+        let config: GameConfig = ron::from_str(text).unwrap();
+        assert!(config.validate().is_err(), "{} is accepted", text);
+    }
+}
+
+#[test]
+fn weather_out_of_order_or_out_of_chance_is_rejected() {
+    for text in [
+        "(weather: (min_spell: -15.0))",
+        "(weather: (min_spell: 20.0, max_spell: 10.0))",
+        "(weather: (easiest_worsen_chance: -0.1))",
+        "(weather: (hardest_worsen_chance: 1.2))",
+    ] {
+        let config: GameConfig = ron::from_str(text).unwrap();
+        assert!(config.validate().is_err(), "{} is accepted", text);
+    }
+}
+
+#[test]
+fn terrain_out_of_order_or_out_of_chance_is_rejected() {
+    for text in [
+        "(terrain: (ground_height: 0.0))",
+        "(terrain: (min_ground_length: 0.0))",
+        "(terrain: (min_ground_length: 700.0, max_ground_length: 640.0))",
+        "(terrain: (min_pit_width: -80.0))",
+        "(terrain: (min_pit_width: 160.0, max_pit_width: 150.0))",
+        "(terrain: (spike_chance: 2.0))",
+        "(terrain: (platform_chance: -1.0))",
+        "(terrain: (platform_width: 0.0))",
+        "(terrain: (platform_height: -60.0))",
+    ] {
+        let config: GameConfig = ron::from_str(text).unwrap();
+        assert!(config.validate().is_err(), "{} is accepted", text);
+    }
+}
@@ -0,0 +1,837 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+use flynn::config::{GameConfig, GameMode};
+use flynn::daylight::TimeOfDay;
+use flynn::headless::HeadlessPlugin;
+use flynn::physics::{self, CollideEvent, Collider, CollisionLayers, RigidBody, Shape};
+use flynn::player::controller::{Action, Controller};
+use flynn::player::dash::{Dash, DASH_TICKS};
+use flynn::player::{movement, Player, PlayerState};
+use flynn::replay::{Recorder, Replay};
+use flynn::world::{
+    Difficulty, Obstacle, Oscillation, ParallaxLayer, Pooled, ScoreBoard, Waypoint, Weather, Wind,
+    WorldSeed,
+};
+use flynn::{ApplicationState, GamePlugin};
+
+const SEED: u64 = 42;
+/// Fixed ticks per second
+const ONE_SECOND: usize = 64;
+
+#[derive(Resource, Default)]
+struct CollisionLog(Vec<CollideEvent>);
+
+fn log_collisions(mut events: EventReader<CollideEvent>, mut log: ResMut<CollisionLog>) {
+    log.0.extend(events.read().copied());
+}
+
+/// Fly toward the next gap, holding the current one while between its walls
+fn autopilot(
+    mut target: Local<f32>,
+    waypoints: Query<&Transform, With<Waypoint>>,
+    walls: Query<&Transform, (With<Obstacle>, Without<Waypoint>)>,
+    mut player: Query<(&RigidBody, &mut Controller), With<Player>>,
+) {
+    let (body, mut controller) = player.single_mut();
+
+    let between_walls = walls
+        .iter()
+        .any(|wall| (wall.translation.x - body.position.x).abs() < 50.0);
+    if !between_walls {
+        if let Some(next_gap) = waypoints
+            .iter()
+            .filter(|waypoint| waypoint.translation.x > body.position.x)
+            .min_by(|a, b| a.translation.x.total_cmp(&b.translation.x))
+        {
+            *target = next_gap.translation.y;
+        }
+    }
+
+    // Hold the jump up to the target, release it to jump again at the apex
+    let below = body.position.y < *target - 30.0;
+    controller.jump_held = below && (!controller.jump_held || body.velocity.y > 0.0);
+}
+
+fn headless_app(autopilot_enabled: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin)
+        .add_plugins(GamePlugin)
+        .insert_resource(WorldSeed::locked(SEED))
+        .init_resource::<CollisionLog>()
+        .add_systems(FixedUpdate, log_collisions.after(physics::collision));
+    if autopilot_enabled {
+        app.add_systems(
+            FixedUpdate,
+            autopilot
+                .before(movement::player_movement)
+                .run_if(in_state(ApplicationState::InGame)),
+        );
+    }
+    // Run the startup, the time only advances from the next update
+    app.update();
+    app
+}
+
+fn send_key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+    app.world_mut().send_event(KeyboardInput {
+        key_code,
+        logical_key: Key::Unidentified(NativeKey::Unidentified),
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+fn tap_key(app: &mut App, key_code: KeyCode) {
+    send_key(app, key_code, ButtonState::Pressed);
+    app.update();
+    send_key(app, key_code, ButtonState::Released);
+    app.update();
+}
+
+fn state(app: &App) -> ApplicationState {
+    app.world()
+        .resource::<State<ApplicationState>>()
+        .get()
+        .clone()
+}
+
+fn score(app: &App) -> u32 {
+    app.world().resource::<ScoreBoard>().score
+}
+
+fn player_position(app: &mut App) -> Vec2 {
+    app.world_mut()
+        .query_filtered::<&RigidBody, With<Player>>()
+        .single(app.world())
+        .position
+}
+
+/// Step until the state is reached
+fn run_until(app: &mut App, expected: ApplicationState, max_updates: usize) {
+    for _ in 0..max_updates {
+        if state(app) == expected {
+            return;
+        }
+        app.update();
+    }
+    panic!("{:?} not reached after {} updates", expected, max_updates);
+}
+
+#[test]
+fn space_starts_the_game() {
+    let mut app = headless_app(false);
+    assert_eq!(state(&app), ApplicationState::LandingScreen);
+
+    tap_key(&mut app, KeyCode::Space);
+    assert_eq!(state(&app), ApplicationState::InGame);
+}
+
+#[test]
+fn falling_on_the_ground_ends_the_game() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+
+    run_until(&mut app, ApplicationState::GameEnding, 10 * ONE_SECOND);
+
+    let log = &app.world().resource::<CollisionLog>().0;
+    let last = log.last().unwrap();
+    assert_eq!(last.layer, CollisionLayers::PLAYER);
+    assert_eq!(last.other_layer, CollisionLayers::OBSTACLE);
+    assert_eq!(score(&app), 0);
+}
+
+#[test]
+fn flying_through_gaps_scores() {
+    let mut app = headless_app(true);
+    tap_key(&mut app, KeyCode::Space);
+
+    // The first obstacle reaches the fox after about 7 seconds
+    for _ in 0..15 * ONE_SECOND {
+        app.update();
+    }
+
+    assert_eq!(state(&app), ApplicationState::InGame);
+    let log = &app.world().resource::<CollisionLog>().0;
+    let waypoints = log
+        .iter()
+        .filter(|event| event.other_layer == CollisionLayers::WAYPOINT)
+        .count();
+    assert!(score(&app) >= 3);
+    assert_eq!(score(&app) as usize, waypoints);
+}
+
+#[test]
+fn restart_resets_the_run() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+    let start_position = player_position(&mut app);
+
+    run_until(&mut app, ApplicationState::GameEnd, 10 * ONE_SECOND);
+    tap_key(&mut app, KeyCode::Space);
+
+    assert_eq!(state(&app), ApplicationState::InGame);
+    assert_eq!(score(&app), 0);
+    assert!(player_position(&mut app).distance(start_position) < 10.0);
+}
+
+#[test]
+fn retry_keeps_the_seed_of_the_run() {
+    let mut app = headless_app(false);
+    app.insert_resource(WorldSeed {
+        value: SEED,
+        locked: false,
+    });
+    tap_key(&mut app, KeyCode::Space);
+
+    run_until(&mut app, ApplicationState::GameEnd, 10 * ONE_SECOND);
+    tap_key(&mut app, KeyCode::KeyR);
+    assert_eq!(state(&app), ApplicationState::InGame);
+    assert_eq!(app.world().resource::<WorldSeed>().value, SEED);
+
+    run_until(&mut app, ApplicationState::GameEnd, 10 * ONE_SECOND);
+    tap_key(&mut app, KeyCode::Space);
+    assert_ne!(app.world().resource::<WorldSeed>().value, SEED);
+}
+
+#[test]
+fn difficulty_ramps_up_and_resets_on_restart() {
+    let mut app = headless_app(true);
+    let easiest = app.world().resource::<GameConfig>().difficulty.easiest;
+    tap_key(&mut app, KeyCode::Space);
+
+    for _ in 0..15 * ONE_SECOND {
+        app.update();
+    }
+    let difficulty = *app.world().resource::<Difficulty>();
+    assert!(difficulty.obstacle_speed > easiest.obstacle_speed);
+    assert!(difficulty.spawn_interval < easiest.spawn_interval);
+    assert!(difficulty.gap_size < easiest.gap_size);
+
+    tap_key(&mut app, KeyCode::Escape);
+    tap_key(&mut app, KeyCode::KeyR);
+    assert_eq!(*app.world().resource::<Difficulty>(), easiest);
+}
+
+#[test]
+fn oscillating_obstacle_returns_to_its_spawn_height() {
+    // Keep the run going for the whole period
+    let mut app = headless_app(true);
+    tap_key(&mut app, KeyCode::Space);
+    let body = app
+        .world_mut()
+        .spawn((
+            TransformBundle::default(),
+            RigidBody::default(),
+            Oscillation {
+                amplitude: 50.0,
+                period: 1.0,
+                age: 0.0,
+            },
+        ))
+        .id();
+    let height = |app: &App| app.world().get::<RigidBody>(body).unwrap().position.y;
+
+    for _ in 0..ONE_SECOND / 4 {
+        app.update();
+    }
+    assert!((height(&app) - 50.0).abs() < 0.1);
+    for _ in 0..3 * ONE_SECOND / 4 {
+        app.update();
+    }
+    assert!(height(&app).abs() < 0.1);
+}
+
+#[test]
+fn obstacles_are_recycled_through_the_pool() {
+    let mut app = headless_app(true);
+    tap_key(&mut app, KeyCode::Space);
+    let pooled = |app: &mut App| {
+        app.world_mut()
+            .query_filtered::<Entity, With<Pooled>>()
+            .iter(app.world())
+            .count()
+    };
+    let initial = pooled(&mut app);
+
+    for _ in 0..15 * ONE_SECOND {
+        app.update();
+    }
+    assert_eq!(state(&app), ApplicationState::InGame);
+    assert!(score(&app) > 0);
+    assert_eq!(pooled(&mut app), initial);
+}
+
+#[test]
+fn wall_sprites_are_sized_apart_from_their_collider() {
+    let mut app = headless_app(true);
+    tap_key(&mut app, KeyCode::Space);
+    // Past the first spawn
+    for _ in 0..3 * ONE_SECOND {
+        app.update();
+    }
+
+    let config = GameConfig::default();
+    let mut walls = app
+        .world_mut()
+        .query_filtered::<(&Sprite, &Collider), (With<Obstacle>, Without<Waypoint>)>();
+    assert!(walls.iter(app.world()).count() > 0);
+    for (sprite, collider) in walls.iter(app.world()) {
+        let Shape::Aabb { half_size } = collider.shape else {
+            panic!("Walls are boxes");
+        };
+        let size = sprite.custom_size.unwrap();
+        assert_eq!(size.x, config.obstacle.sprite_width);
+        assert_eq!(half_size.x * 2.0, config.obstacle.width);
+        assert_eq!(size.y, half_size.y * 2.0);
+    }
+}
+
+#[test]
+fn wind_pushes_the_fox() {
+    let mut calm = headless_app(false);
+    let mut windy = headless_app(false);
+    tap_key(&mut calm, KeyCode::Space);
+    tap_key(&mut windy, KeyCode::Space);
+    for (app, acceleration) in [
+        (&mut calm, Vec2::ZERO),
+        (&mut windy, Vec2::new(-300.0, 300.0)),
+    ] {
+        let mut wind = app.world_mut().resource_mut::<Wind>();
+        wind.acceleration = acceleration;
+        wind.upcoming = acceleration;
+    }
+
+    for _ in 0..ONE_SECOND / 2 {
+        calm.update();
+        windy.update();
+    }
+    let calm_position = player_position(&mut calm);
+    let windy_position = player_position(&mut windy);
+    assert!(windy_position.x < calm_position.x);
+    assert!(windy_position.y > calm_position.y);
+}
+
+#[test]
+fn wind_starts_from_the_loaded_configuration() {
+    let mut app = headless_app(false);
+    app.world_mut()
+        .resource_mut::<GameConfig>()
+        .wind
+        .lull_strength = 100.0;
+    tap_key(&mut app, KeyCode::Space);
+    assert_eq!(
+        app.world().resource::<Wind>().upcoming,
+        Vec2::new(-100.0, 0.0)
+    );
+
+    app.world_mut()
+        .resource_mut::<GameConfig>()
+        .wind
+        .lull_strength = 200.0;
+    tap_key(&mut app, KeyCode::Escape);
+    tap_key(&mut app, KeyCode::KeyR);
+    assert_eq!(
+        app.world().resource::<Wind>().upcoming,
+        Vec2::new(-200.0, 0.0)
+    );
+}
+
+#[test]
+fn weather_worsens_and_clears_on_restart() {
+    let mut app = headless_app(true);
+    let mut config = app.world_mut().resource_mut::<GameConfig>();
+    config.weather.min_spell = 1.0;
+    config.weather.max_spell = 1.0;
+    config.weather.easiest_worsen_chance = 1.0;
+    config.weather.hardest_worsen_chance = 1.0;
+    tap_key(&mut app, KeyCode::Space);
+
+    // Three spells of the loaded configuration, far shorter than the default
+    for _ in 0..3 * ONE_SECOND + 2 {
+        app.update();
+    }
+    assert_eq!(state(&app), ApplicationState::InGame);
+    assert_eq!(*app.world().resource::<Weather>(), Weather::Blizzard);
+
+    tap_key(&mut app, KeyCode::Escape);
+    tap_key(&mut app, KeyCode::KeyR);
+    assert_eq!(*app.world().resource::<Weather>(), Weather::Clear);
+}
+
+#[test]
+fn night_falls_over_the_run_and_restart_brings_dawn() {
+    let mut app = headless_app(true);
+    app.world_mut()
+        .resource_mut::<GameConfig>()
+        .daylight
+        .day_length = 4.0;
+    tap_key(&mut app, KeyCode::Space);
+
+    for _ in 0..3 * ONE_SECOND {
+        app.update();
+    }
+    let fox = app
+        .world_mut()
+        .query_filtered::<&Sprite, With<Player>>()
+        .single(app.world())
+        .color
+        .to_linear();
+    assert!(fox.red < 0.5);
+    assert!(fox.blue > fox.red);
+
+    tap_key(&mut app, KeyCode::Escape);
+    tap_key(&mut app, KeyCode::KeyR);
+    assert_eq!(*app.world().resource::<TimeOfDay>(), TimeOfDay::default());
+}
+
+#[test]
+fn dash_bursts_once_until_refilled() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+    let start = player_position(&mut app);
+    let mut controller = app
+        .world_mut()
+        .query_filtered::<&mut Controller, With<Player>>()
+        .single_mut(app.world_mut());
+    controller.action = Action::Dash;
+    controller.direction = Vec2::new(1.0, 1.0);
+
+    for _ in 0..DASH_TICKS {
+        app.update();
+    }
+    // Straight diagonal, without gravity
+    let dashed = player_position(&mut app) - start;
+    assert!(dashed.x > 50.0);
+    assert!((dashed.x - dashed.y).abs() < 0.01);
+
+    // Used up, holding the dash has no effect until the next gap
+    app.update();
+    let state = *app
+        .world_mut()
+        .query_filtered::<&PlayerState, With<Player>>()
+        .single(app.world());
+    assert_eq!(state, PlayerState::Running);
+
+    // Flying through a gap refills it
+    let position = player_position(&mut app);
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        Obstacle,
+        Waypoint,
+        Collider::aabb(Vec2::new(10.0, 100.0))
+            .with_layers(CollisionLayers::WAYPOINT, CollisionLayers::NONE),
+        RigidBody {
+            position,
+            ..default()
+        },
+    ));
+    app.update();
+    let dash = app
+        .world_mut()
+        .query_filtered::<&Dash, With<Player>>()
+        .single(app.world());
+    assert!(dash.available);
+
+    // Still held, it only dashes again on the next press
+    app.update();
+    let dash = app
+        .world_mut()
+        .query_filtered::<&Dash, With<Player>>()
+        .single(app.world());
+    assert!(dash.available && !dash.is_dashing());
+
+    let set_action = |app: &mut App, action: Action| {
+        app.world_mut()
+            .query_filtered::<&mut Controller, With<Player>>()
+            .single_mut(app.world_mut())
+            .action = action;
+    };
+    set_action(&mut app, Action::None);
+    app.update();
+    set_action(&mut app, Action::Dash);
+    app.update();
+    let dash = app
+        .world_mut()
+        .query_filtered::<&Dash, With<Player>>()
+        .single(app.world());
+    assert!(dash.is_dashing());
+}
+
+#[test]
+fn layers_are_matched_as_bit_sets() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+
+    // An obstacle on an extra layer still ends the run
+    let position = player_position(&mut app) + Vec2::new(60.0, 0.0);
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        Obstacle,
+        Collider::aabb(Vec2::new(20.0, 100.0)).with_layers(
+            CollisionLayers::OBSTACLE | CollisionLayers(1 << 7),
+            CollisionLayers::NONE,
+        ),
+        RigidBody {
+            position,
+            velocity: Vec2::new(-300.0, 0.0),
+            ..default()
+        },
+    ));
+    run_until(&mut app, ApplicationState::GameEnding, ONE_SECOND);
+}
+
+#[test]
+fn only_the_fox_scores_through_the_gaps() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+
+    // Something else than the fox going through a gap
+    let position = Vec2::new(300.0, 0.0);
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        Obstacle,
+        Waypoint,
+        Collider::aabb(Vec2::new(10.0, 100.0))
+            .with_layers(CollisionLayers::WAYPOINT, CollisionLayers::NONE),
+        RigidBody {
+            position,
+            ..default()
+        },
+    ));
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        Collider::aabb(Vec2::splat(10.0))
+            .with_layers(CollisionLayers::OBSTACLE, CollisionLayers::WAYPOINT),
+        RigidBody {
+            position,
+            ..default()
+        },
+    ));
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let log = &app.world().resource::<CollisionLog>().0;
+    assert!(log
+        .iter()
+        .any(|event| event.other_layer == CollisionLayers::WAYPOINT));
+    assert_eq!(score(&app), 0);
+}
+
+#[test]
+fn dashing_while_holding_the_jump_does_not_jump_again() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+    let set_controller = |app: &mut App, action: Action| {
+        let mut controller = app
+            .world_mut()
+            .query_filtered::<&mut Controller, With<Player>>()
+            .single_mut(app.world_mut());
+        controller.action = action;
+        controller.jump_held = true;
+        controller.direction = Vec2::X;
+    };
+
+    set_controller(&mut app, Action::None);
+    for _ in 0..4 {
+        app.update();
+    }
+    set_controller(&mut app, Action::Dash);
+    for _ in 0..DASH_TICKS {
+        app.update();
+    }
+    // Releasing the dash with the jump still held
+    set_controller(&mut app, Action::None);
+    app.update();
+    let (body, state) = app
+        .world_mut()
+        .query_filtered::<(&RigidBody, &PlayerState), With<Player>>()
+        .single(app.world());
+    assert_ne!(*state, PlayerState::Jumping);
+    assert!(body.velocity.y <= 0.0);
+}
+
+/// Tall wall coming at the fox, with only room below it
+fn spawn_tall_wall(app: &mut App) {
+    let position = Vec2::new(80.0, 100.0);
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+        Obstacle,
+        Collider::aabb(Vec2::new(10.0, 200.0))
+            .with_layers(CollisionLayers::OBSTACLE, CollisionLayers::NONE),
+        RigidBody {
+            position,
+            velocity: Vec2::new(-120.0, 0.0),
+            ..default()
+        },
+    ));
+}
+
+#[test]
+fn wall_sides_are_slid_down_with_wall_slide() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+    spawn_tall_wall(&mut app);
+    run_until(&mut app, ApplicationState::GameEnding, ONE_SECOND);
+
+    let mut app = headless_app(false);
+    app.world_mut()
+        .resource_mut::<GameConfig>()
+        .player
+        .wall_slide = true;
+    tap_key(&mut app, KeyCode::Space);
+    spawn_tall_wall(&mut app);
+    let player = |app: &mut App| {
+        let (body, state) = app
+            .world_mut()
+            .query_filtered::<(&RigidBody, &PlayerState), With<Player>>()
+            .single(app.world());
+        (body.velocity, *state)
+    };
+    for _ in 0..ONE_SECOND / 2 {
+        app.update();
+    }
+    assert_eq!(state(&app), ApplicationState::InGame);
+    let (velocity, state) = player(&mut app);
+    assert_eq!(state, PlayerState::Sliding);
+    // Carried along by the wall
+    assert!((velocity.x + 120.0).abs() < 1.0);
+    let slide_speed = GameConfig::default().player.wall_slide_speed;
+    assert!(velocity.y >= -slide_speed - 30.0);
+
+    // Kicked off the wall
+    let mut controller = app
+        .world_mut()
+        .query_filtered::<&mut Controller, With<Player>>()
+        .single_mut(app.world_mut());
+    controller.jump_held = true;
+    app.update();
+    let (velocity, state) = player(&mut app);
+    assert_eq!(state, PlayerState::Jumping);
+    assert!(velocity.x < -200.0 && velocity.y > 0.0);
+}
+
+#[test]
+fn running_mode_runs_on_the_ground_until_a_pit() {
+    let mut app = headless_app(false);
+    app.world_mut().resource_mut::<GameConfig>().mode = GameMode::Running;
+    tap_key(&mut app, KeyCode::Space);
+    let config = GameConfig::default();
+    let player = |app: &mut App| {
+        let (body, state) = app
+            .world_mut()
+            .query_filtered::<(&RigidBody, &PlayerState), With<Player>>()
+            .single(app.world());
+        (body.position, body.velocity, *state)
+    };
+    let jump = |app: &mut App| {
+        let mut controller = app
+            .world_mut()
+            .query_filtered::<&mut Controller, With<Player>>()
+            .single_mut(app.world_mut());
+        controller.jump_held = true;
+        app.update();
+        let mut controller = app
+            .world_mut()
+            .query_filtered::<&mut Controller, With<Player>>()
+            .single_mut(app.world_mut());
+        controller.jump_held = false;
+    };
+
+    for _ in 0..ONE_SECOND {
+        app.update();
+    }
+    // Standing on the ground
+    let ground_top = config.world.bottom() + config.terrain.ground_height;
+    let (position, velocity, state) = player(&mut app);
+    assert_eq!(state, PlayerState::Running);
+    assert!((position.y - 22.0 - ground_top).abs() < 1.0);
+    assert_eq!(velocity.y, 0.0);
+
+    // Jumping from the ground only
+    jump(&mut app);
+    let (_, velocity, _) = player(&mut app);
+    assert!(velocity.y > config.player.jump_velocity);
+    for _ in 0..ONE_SECOND / 8 {
+        app.update();
+    }
+    jump(&mut app);
+    let (_, after, _) = player(&mut app);
+    assert!(after.y < velocity.y);
+
+    // Falls in the first pit
+    run_until(&mut app, ApplicationState::GameEnding, 15 * ONE_SECOND);
+}
+
+#[test]
+fn one_way_platform_is_only_solid_from_above() {
+    // Highest head and last feet height of the fox
+    let mut runs = Vec::new();
+    for one_way in [false, true] {
+        let mut app = headless_app(false);
+        tap_key(&mut app, KeyCode::Space);
+        // Right above the head of the fox
+        let collider = Collider::aabb(Vec2::new(100.0, 5.0))
+            .with_layers(CollisionLayers::GROUND, CollisionLayers::NONE);
+        app.world_mut().spawn((
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 70.0, 0.0)),
+            if one_way {
+                collider.one_way()
+            } else {
+                collider
+            },
+        ));
+        let mut controller = app
+            .world_mut()
+            .query_filtered::<&mut Controller, With<Player>>()
+            .single_mut(app.world_mut());
+        controller.jump_held = true;
+        let mut highest = f32::NEG_INFINITY;
+        for _ in 0..ONE_SECOND / 2 {
+            app.update();
+            highest = highest.max(player_position(&mut app).y + 22.0);
+        }
+        runs.push((highest, player_position(&mut app).y - 22.0));
+    }
+    // Bumped its head, then jumped through and stood on the top
+    assert!(runs[0].0 <= 65.0);
+    assert!(runs[1].0 > 75.0);
+    assert!((runs[1].1 - 75.0).abs() < 0.1);
+}
+
+#[test]
+fn releasing_the_jump_early_jumps_lower() {
+    let mut apex = Vec::new();
+    for held_ticks in [2, ONE_SECOND] {
+        let mut app = headless_app(false);
+        tap_key(&mut app, KeyCode::Space);
+        let mut highest = f32::NEG_INFINITY;
+        for tick in 0..ONE_SECOND / 2 {
+            let mut controller = app
+                .world_mut()
+                .query_filtered::<&mut Controller, With<Player>>()
+                .single_mut(app.world_mut());
+            controller.jump_held = tick < held_ticks;
+            app.update();
+            highest = highest.max(player_position(&mut app).y);
+        }
+        apex.push(highest);
+        // Holding the jump no longer pins the fox upward
+        assert!(player_position(&mut app).y < highest);
+    }
+    assert!(apex[0] < apex[1]);
+}
+
+#[test]
+fn pause_freezes_the_game() {
+    let mut app = headless_app(false);
+    tap_key(&mut app, KeyCode::Space);
+
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(state(&app), ApplicationState::Paused);
+    let paused_position = player_position(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(player_position(&mut app), paused_position);
+
+    tap_key(&mut app, KeyCode::Escape);
+    assert_eq!(state(&app), ApplicationState::InGame);
+    app.update();
+    assert_ne!(player_position(&mut app), paused_position);
+}
+
+#[test]
+fn background_scrolls_with_the_game() {
+    let mut app = headless_app(true);
+    let mut layers = app
+        .world_mut()
+        .query_filtered::<&Transform, With<ParallaxLayer>>();
+    let mut positions = |app: &mut App| -> Vec<f32> {
+        layers
+            .iter(app.world())
+            .map(|transform| transform.translation.x)
+            .collect()
+    };
+    app.update();
+    let start = positions(&mut app);
+    assert_eq!(start.len(), 4);
+
+    tap_key(&mut app, KeyCode::Space);
+    for _ in 0..ONE_SECOND {
+        app.update();
+    }
+    let scrolled = positions(&mut app);
+    assert!(start.iter().zip(&scrolled).all(|(start, now)| now < start));
+
+    tap_key(&mut app, KeyCode::Escape);
+    let paused = positions(&mut app);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(positions(&mut app), paused);
+}
+
+#[test]
+fn same_seed_and_inputs_give_the_same_run() {
+    let mut first = headless_app(true);
+    let mut second = headless_app(true);
+    tap_key(&mut first, KeyCode::Space);
+    tap_key(&mut second, KeyCode::Space);
+
+    for _ in 0..10 * ONE_SECOND {
+        first.update();
+        second.update();
+        assert_eq!(player_position(&mut first), player_position(&mut second));
+    }
+    assert_eq!(score(&first), score(&second));
+}
+
+#[test]
+fn replaying_a_recording_gives_the_same_run() {
+    let mut recorded = headless_app(true);
+    tap_key(&mut recorded, KeyCode::Space);
+    run_until(
+        &mut recorded,
+        ApplicationState::GameEnding,
+        120 * ONE_SECOND,
+    );
+    let recording = recorded.world().resource::<Recorder>().recording.clone();
+    assert_eq!(recording.seed, SEED);
+    assert!(score(&recorded) > 0);
+
+    let mut replayed = headless_app(false);
+    replayed.insert_resource(Replay::new(recording.clone()));
+    tap_key(&mut replayed, KeyCode::Space);
+    run_until(
+        &mut replayed,
+        ApplicationState::GameEnding,
+        120 * ONE_SECOND,
+    );
+
+    // Ended on the same tick, at the same place and with the same score
+    let replayed_ticks = replayed
+        .world()
+        .resource::<Recorder>()
+        .recording
+        .inputs
+        .len();
+    assert_eq!(replayed_ticks, recording.inputs.len());
+    assert_eq!(
+        player_position(&mut replayed),
+        player_position(&mut recorded)
+    );
+    assert_eq!(score(&replayed), score(&recorded));
+    let player_state = |app: &mut App| {
+        *app.world_mut()
+            .query_filtered::<&PlayerState, With<Player>>()
+            .single(app.world())
+    };
+    assert_eq!(player_state(&mut replayed), player_state(&mut recorded));
+}
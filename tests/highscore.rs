@@ -0,0 +1,80 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use flynn::highscore::{format_date, HighScore, HighScores};
+
+fn entry(score: u32, date: u64) -> HighScore {
+    HighScore {
+        score,
+        date,
+        seed: 42,
+        duration: 12.5,
+    }
+}
+
+#[test]
+fn table_round_trips_through_text() {
+    let mut highscores = HighScores::default();
+    highscores.insert(entry(12, 1_700_000_000));
+    highscores.insert(entry(30, 1_700_000_100));
+
+    let parsed = HighScores::parse(&highscores.serialize());
+    assert_eq!(parsed.entries, highscores.entries);
+    assert_eq!(parsed.last_entry, None);
+}
+
+#[test]
+fn malformed_lines_are_skipped() {
+    let data = "30;1700000000;42;12.5\n\
+                not a high-score\n\
+                20;1700000000;42\n\
+                -5;1700000000;42;12.5\n\
+                10;1700000000;42;8.0\n";
+    let highscores = HighScores::parse(data);
+    let scores: Vec<u32> = highscores.entries.iter().map(|entry| entry.score).collect();
+    assert_eq!(scores, [30, 10]);
+}
+
+#[test]
+fn only_the_top_five_are_kept() {
+    let mut highscores = HighScores::default();
+    for score in [5, 40, 10, 30, 20, 50] {
+        highscores.insert(entry(score, 0));
+    }
+    let scores: Vec<u32> = highscores.entries.iter().map(|entry| entry.score).collect();
+    assert_eq!(scores, [50, 40, 30, 20, 10]);
+
+    assert_eq!(highscores.insert(entry(1, 0)), None);
+    assert_eq!(highscores.last_entry, None);
+    assert_eq!(highscores.insert(entry(35, 0)), Some(2));
+    assert_eq!(highscores.entries.len(), 5);
+    assert_eq!(highscores.entries.last().unwrap().score, 20);
+}
+
+#[test]
+fn oldest_run_keeps_its_rank_on_equal_scores() {
+    let mut highscores = HighScores::default();
+    highscores.insert(entry(20, 1));
+    highscores.insert(entry(10, 2));
+    assert_eq!(highscores.insert(entry(20, 3)), Some(1));
+
+    let dates: Vec<u64> = highscores.entries.iter().map(|entry| entry.date).collect();
+    assert_eq!(dates, [1, 3, 2]);
+
+    // Behind every run of the same score, even at the end of a full table
+    for _ in 0..2 {
+        highscores.insert(entry(10, 4));
+    }
+    assert_eq!(highscores.insert(entry(10, 5)), None);
+}
+
+#[test]
+fn dates_are_formatted_as_year_month_day() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(86_399), "1970-01-01");
+    assert_eq!(format_date(946_598_400), "1999-12-31");
+    assert_eq!(format_date(951_782_400), "2000-02-29");
+    assert_eq!(format_date(1_709_164_800), "2024-02-29");
+    assert_eq!(format_date(1_735_603_200 + 86_399), "2024-12-31");
+}
@@ -0,0 +1,75 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use flynn::config::PlayerConfig;
+use flynn::player::jump::{Jump, JumpAction};
+
+const TICK: f32 = 1.0 / 64.0;
+
+/// Jumps only from the ground, as when running
+fn ground_config() -> PlayerConfig {
+    PlayerConfig {
+        air_jump: false,
+        ..PlayerConfig::default()
+    }
+}
+
+fn update(jump: &mut Jump, held: bool, config: &PlayerConfig) -> JumpAction {
+    jump.update(held, true, config.air_jump, TICK, config)
+}
+
+fn run(jump: &mut Jump, held: bool, ticks: usize, config: &PlayerConfig) -> Vec<JumpAction> {
+    (0..ticks).map(|_| update(jump, held, config)).collect()
+}
+
+#[test]
+fn jump_triggers_on_the_press_only() {
+    let config = PlayerConfig::default();
+    let mut jump = Jump::default();
+
+    let actions = run(&mut jump, true, 10, &config);
+    assert_eq!(actions[0], JumpAction::Start);
+    assert!(actions[1..]
+        .iter()
+        .all(|action| *action == JumpAction::None));
+    assert_eq!(update(&mut jump, false, &config), JumpAction::Cut);
+    assert_eq!(update(&mut jump, true, &config), JumpAction::Start);
+}
+
+#[test]
+fn press_before_landing_is_buffered() {
+    let config = ground_config();
+    let mut jump = Jump::default();
+    run(&mut jump, false, 20, &config);
+
+    // Too far from the ground
+    assert_eq!(update(&mut jump, true, &config), JumpAction::None);
+    run(&mut jump, true, 3, &config);
+    jump.land();
+    assert_eq!(update(&mut jump, true, &config), JumpAction::Start);
+
+    // Older than the buffer
+    run(&mut jump, false, 20, &config);
+    update(&mut jump, true, &config);
+    run(&mut jump, true, 20, &config);
+    jump.land();
+    assert_eq!(update(&mut jump, true, &config), JumpAction::None);
+}
+
+#[test]
+fn jump_is_possible_shortly_after_leaving_the_ground() {
+    let config = ground_config();
+    let mut jump = Jump::default();
+    jump.land();
+    run(&mut jump, false, 4, &config);
+    assert_eq!(update(&mut jump, true, &config), JumpAction::Start);
+
+    // Only once
+    update(&mut jump, false, &config);
+    assert_eq!(update(&mut jump, true, &config), JumpAction::None);
+
+    jump.land();
+    run(&mut jump, false, 10, &config);
+    assert_eq!(update(&mut jump, true, &config), JumpAction::None);
+}
@@ -0,0 +1,71 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use flynn::world::{choose_pattern, default_patterns, DifficultyCurve, Gate, Motion};
+
+#[test]
+fn only_the_patterns_of_the_level_are_picked() {
+    let patterns = default_patterns();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for _ in 0..100 {
+        let pattern = choose_pattern(&patterns, 0.0, &mut rng).unwrap();
+        assert_eq!(pattern.min_level, 0.0);
+    }
+}
+
+#[test]
+fn every_pattern_is_picked_at_the_hardest_level() {
+    let patterns = default_patterns();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut picked: Vec<&str> = (0..1000)
+        .map(|_| {
+            choose_pattern(&patterns, 1.0, &mut rng)
+                .unwrap()
+                .name
+                .as_str()
+        })
+        .collect();
+    picked.sort();
+    picked.dedup();
+    assert_eq!(picked.len(), patterns.len());
+}
+
+#[test]
+fn mirrored_pattern_flips_the_gaps() {
+    let staircase = default_patterns()
+        .into_iter()
+        .find(|pattern| pattern.name == "staircase")
+        .unwrap();
+    let (low, high) = staircase.gap_range();
+
+    assert_eq!(staircase.mirrored().gap_range(), (-high, -low));
+    assert_eq!(staircase.mirrored().length(), staircase.length());
+}
+
+#[test]
+fn closing_gate_closes_its_gap_at_the_peak_of_the_period() {
+    let closing_gate = default_patterns()
+        .into_iter()
+        .find(|pattern| pattern.name == "closing-gate")
+        .unwrap();
+    let column = &closing_gate.columns[0];
+    let Some(Motion::Gate { travel, period }) = column.motion else {
+        panic!("closing-gate is not a gate");
+    };
+
+    let curve = DifficultyCurve::default();
+    for gap_size in [curve.easiest.gap_size, curve.hardest.gap_size] {
+        let gap = column.gaps[0].size * gap_size;
+        let [bottom, top] = Gate::outer_walls(travel, period, gap, gap);
+        let opening = |age: f32| gap - bottom.offset(age) + top.offset(age);
+
+        assert_eq!(opening(period / 4.0), gap);
+        assert!(opening(period * 3.0 / 4.0) <= 0.01);
+    }
+}
@@ -0,0 +1,147 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::prelude::*;
+
+use flynn::physics::{resolve, sweep, RigidBody, Shape};
+
+const FOX: Shape = Shape::Aabb {
+    half_size: Vec2::splat(24.0),
+};
+const WALL: Shape = Shape::Aabb {
+    half_size: Vec2::new(10.0, 50.0),
+};
+
+#[test]
+fn fast_body_does_not_tunnel_through_a_wall() {
+    // Ends the tick on the other side of the wall
+    let contact = sweep(
+        Vec2::new(-60.0, 0.0),
+        &FOX,
+        Vec2::new(200.0, 0.0),
+        Vec2::ZERO,
+        &WALL,
+    )
+    .unwrap();
+
+    assert!((contact.time_of_impact - 26.0 / 200.0).abs() < 1e-6);
+    assert_eq!(contact.normal, Vec2::NEG_X);
+}
+
+#[test]
+fn landing_on_top_reports_an_up_normal() {
+    let contact = sweep(
+        Vec2::new(0.0, 80.0),
+        &FOX,
+        Vec2::new(0.0, -20.0),
+        Vec2::ZERO,
+        &WALL,
+    )
+    .unwrap();
+
+    assert!((contact.time_of_impact - 0.3).abs() < 1e-6);
+    assert_eq!(contact.normal, Vec2::Y);
+}
+
+#[test]
+fn body_passing_by_does_not_collide() {
+    let above = Vec2::new(-60.0, 100.0);
+    assert_eq!(
+        sweep(above, &FOX, Vec2::new(200.0, 0.0), Vec2::ZERO, &WALL),
+        None
+    );
+
+    // Too slow to reach the wall during the tick
+    let before = Vec2::new(-60.0, 0.0);
+    assert_eq!(
+        sweep(before, &FOX, Vec2::new(20.0, 0.0), Vec2::ZERO, &WALL),
+        None
+    );
+}
+
+#[test]
+fn overlapping_bodies_touch_at_the_start_of_the_tick() {
+    let contact = sweep(Vec2::new(-30.0, 0.0), &FOX, Vec2::ZERO, Vec2::ZERO, &WALL).unwrap();
+
+    assert_eq!(contact.time_of_impact, 0.0);
+    assert_eq!(contact.normal, Vec2::NEG_X);
+}
+
+#[test]
+fn circle_slides_past_the_corner_of_a_box() {
+    let ball = Shape::Circle { radius: 10.0 };
+
+    // Crosses the corner of the box grown by the radius, but not its rounding
+    let past_corner = sweep(
+        Vec2::new(-38.0, 38.0),
+        &ball,
+        Vec2::new(40.0, 40.0),
+        Vec2::ZERO,
+        &WALL,
+    );
+    assert_eq!(past_corner, None);
+
+    let contact = sweep(
+        Vec2::new(-30.0, 55.0),
+        &ball,
+        Vec2::new(60.0, 0.0),
+        Vec2::ZERO,
+        &WALL,
+    )
+    .unwrap();
+    assert!(contact.normal.x < 0.0 && contact.normal.y > 0.0);
+}
+
+#[test]
+fn capsule_is_taller_than_its_circle() {
+    let capsule = Shape::Capsule {
+        half_length: 20.0,
+        radius: 10.0,
+    };
+    let circle = Shape::Circle { radius: 10.0 };
+    // Falling toward a wall whose top is 30 units below
+    let start = Vec2::new(0.0, 90.0);
+    let displacement = Vec2::new(0.0, -20.0);
+
+    assert_eq!(sweep(start, &circle, displacement, Vec2::ZERO, &WALL), None);
+    let contact = sweep(start, &capsule, displacement, Vec2::ZERO, &WALL).unwrap();
+    assert_eq!(contact.normal, Vec2::Y);
+}
+
+#[test]
+fn resolve_pushes_out_and_keeps_the_motion_along_the_surface() {
+    // Landed 4 units into the top of the wall
+    let mut body = RigidBody {
+        position: Vec2::new(0.0, 70.0),
+        velocity: Vec2::new(100.0, -300.0),
+        ..default()
+    };
+    let center = body.position;
+    resolve(
+        &mut body,
+        center,
+        &FOX,
+        (Vec2::ZERO, &WALL, Vec2::ZERO),
+        Vec2::Y,
+    );
+    assert_eq!(body.position, Vec2::new(0.0, 74.0));
+    assert_eq!(body.velocity, Vec2::new(100.0, 0.0));
+
+    // Moving away from the wall, faster than it
+    let mut body = RigidBody {
+        position: Vec2::new(-34.0, 0.0),
+        velocity: Vec2::new(-50.0, 0.0),
+        ..default()
+    };
+    let center = body.position;
+    resolve(
+        &mut body,
+        center,
+        &FOX,
+        (Vec2::ZERO, &WALL, Vec2::new(-20.0, 0.0)),
+        Vec2::NEG_X,
+    );
+    assert_eq!(body.position, Vec2::new(-34.0, 0.0));
+    assert_eq!(body.velocity, Vec2::new(-50.0, 0.0));
+}
@@ -0,0 +1,51 @@
+/* SPDX-License-Identifier: MIT
+ * Copyright (c) 2024 Louis Mayencourt
+ */
+
+use bevy::math::IVec2;
+
+use flynn::player::controller::Action;
+use flynn::replay::{InputRecording, TickInput};
+
+fn input(action: Action, jump_held: bool, x: i32, y: i32) -> TickInput {
+    TickInput {
+        action,
+        jump_held,
+        direction: IVec2::new(x, y),
+    }
+}
+
+#[test]
+fn recording_round_trips_through_bytes() {
+    let recording = InputRecording {
+        seed: 42,
+        inputs: vec![
+            input(Action::None, false, 0, 0),
+            input(Action::None, false, 0, 0),
+            input(Action::None, true, 0, 1),
+            input(Action::Dash, true, -1, 1),
+            input(Action::Dash, false, 1, -1),
+        ],
+    };
+    let bytes = recording.to_bytes();
+    assert_eq!(InputRecording::from_bytes(&bytes), Some(recording));
+}
+
+#[test]
+fn recordings_of_older_versions_are_rejected() {
+    let mut bytes = b"FLYN\x05".to_vec();
+    bytes.extend_from_slice(&7u64.to_le_bytes());
+    bytes.extend_from_slice(&[1, 3, 0, 0, 0]);
+    assert_eq!(InputRecording::from_bytes(&bytes), None);
+
+    bytes[4] = 6;
+    assert!(InputRecording::from_bytes(&bytes).is_some());
+}
+
+#[test]
+fn runs_too_long_are_rejected() {
+    let mut bytes = b"FLYN\x06".to_vec();
+    bytes.extend_from_slice(&7u64.to_le_bytes());
+    bytes.extend_from_slice(&[0, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(InputRecording::from_bytes(&bytes), None);
+}